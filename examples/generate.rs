@@ -0,0 +1,27 @@
+//! Prints a random input for a day, with the default settings.
+//! Run with `cargo run --example generate -- <day> [--seed N] > input/dayN.txt`
+
+use aoc2023_rust::flag_value;
+use aoc2023_rust::generate::*;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let seed = flag_value(&args, "--seed").map_or(0, |seed| seed.parse().expect("Seed should be a number"));
+
+    let generator: Box<dyn InputGenerator> = match args.get(1).map(String::as_str) {
+        Some("1") => Box::new(Day1::default()),
+        Some("2") => Box::new(Day2::default()),
+        Some("3") => Box::new(Day3::default()),
+        Some("4") => Box::new(Day4::default()),
+        Some("5") => Box::new(Day5::default()),
+        Some("6") => Box::new(Day6::default()),
+        Some("7") => Box::new(Day7::default()),
+        Some("8") => Box::new(Day8::default()),
+        Some("9") => Box::new(Day9::default()),
+        Some("10") => Box::new(Day10::default()),
+        Some("11") => Box::new(Day11::default()),
+        _ => panic!("Usage: generate <day from 1 to 11> [--seed N]")
+    };
+
+    print!("{}", generator.generate_seeded(seed));
+}
@@ -0,0 +1,69 @@
+//! Compares memory usage of `read_input_lines` with the borrowed `read_input`
+//! on a generated multi-megabyte input.
+//! Run with `cargo run --release --example input_reading`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use aoc2023_rust::*;
+
+/// Counts allocations and allocated bytes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let (allocations, bytes) = (ALLOCATIONS.load(Ordering::Relaxed), ALLOCATED_BYTES.load(Ordering::Relaxed));
+    let start = Instant::now();
+    let result = f();
+    println!("{:<24} {:>10} allocations {:>12} bytes {:>10.2?}",
+             name,
+             ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+             ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+             start.elapsed());
+    result
+}
+
+fn main() {
+    const WIDTH: usize = 140;
+    const HEIGHT: usize = 100_000;
+
+    let path = std::env::temp_dir().join("aoc2023_input_reading.txt");
+    let line = ".#".repeat(WIDTH / 2);
+    std::fs::write(&path, format!("{line}\n").repeat(HEIGHT)).unwrap();
+    let path = path.to_str().unwrap();
+
+    let count_lines = measure("read_input_lines", || {
+        read_input_lines(path).iter().filter(|l| l.starts_with('.')).count()
+    });
+    let count_borrowed = measure("read_input", || {
+        read_input(path).lines().filter(|l| l.starts_with('.')).count()
+    });
+    assert!(count_lines == count_borrowed);
+
+    measure("board from lines", || {
+        let lines = read_input_lines(path);
+        let input = lines.join("").into_bytes();
+        ndarray::Array2::from_shape_vec((lines.len(), lines[0].len()), input).unwrap()
+    });
+    measure("read_2d_board", || read_2d_board(path));
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,56 @@
+//! Compares finding the first and the last spelled digit (day 1, part 2)
+//! with `str::match_indices` per pattern and with a single `AhoCorasick` automaton.
+//! Run with `cargo run --release --example multi_pattern`
+
+use std::time::Instant;
+
+use aoc2023_rust::aho_corasick::AhoCorasick;
+use itertools::{Itertools, MinMaxResult};
+use rand::{Rng, SeedableRng};
+
+const PATTERNS: [&str; 18] = [
+    "1", "2", "3", "4", "5", "6", "7", "8", "9",
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"
+];
+
+fn first_last_match_indices(line: &str) -> Option<(usize, usize)> {
+    let all_matches = PATTERNS
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, pattern)| line.match_indices(pattern).map(move |(pos, _)| (pos, idx)));
+
+    match all_matches.minmax_by_key(|(pos, _)| *pos) {
+        MinMaxResult::NoElements => None,
+        MinMaxResult::OneElement((_, x)) => Some((x, x)),
+        MinMaxResult::MinMax((_, x), (_, y)) => Some((x, y))
+    }
+}
+
+fn first_last_aho_corasick(matcher: &AhoCorasick, line: &str) -> Option<(usize, usize)> {
+    let first = matcher.find_leftmost(line.as_bytes())?;
+    let last = matcher.find_rightmost(line.as_bytes())?;
+    Some((first.pattern, last.pattern))
+}
+
+fn main() {
+    const LINES: usize = 200_000;
+    const LINE_LENGTH: usize = 60;
+
+    // mostly letters from the spelled digits, so there are many partial matches
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let alphabet = b"onetwhrfuivsxg1234567890abc";
+    let lines = (0..LINES)
+        .map(|_| (0..LINE_LENGTH).map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char).collect::<String>())
+        .collect_vec();
+
+    let start = Instant::now();
+    let expected = lines.iter().map(|line| first_last_match_indices(line)).collect_vec();
+    println!("match_indices: {:>10.2?}", start.elapsed());
+
+    let start = Instant::now();
+    let matcher = AhoCorasick::new(&PATTERNS);
+    let result = lines.iter().map(|line| first_last_aho_corasick(&matcher, line)).collect_vec();
+    println!("Aho-Corasick:  {:>10.2?}", start.elapsed());
+
+    assert!(result == expected);
+}
@@ -0,0 +1,55 @@
+//! Compares counting the winning numbers of a scratchcard (day 4)
+//! with `Vec::contains` and with `NumberSet::common_count`.
+//! Numbers below 128 are several times faster as bits, short lists of larger numbers
+//! are compared pair by pair, about as fast as `Vec::contains`,
+//! and long lists are faster as sets, even counting the time to build them.
+//! Run with `cargo run --release --example scratchcards`
+
+use std::time::Instant;
+
+use aoc2023_rust::number_set::NumberSet;
+use itertools::Itertools;
+use rand::{Rng, SeedableRng};
+use rand::seq::index::sample;
+
+const CARDS: usize = 200_000;
+
+/// Cards with `winning` and `held` distinct numbers below `max`
+fn random_cards(rng: &mut impl Rng, winning: usize, held: usize, max: usize) -> Vec<(Vec<usize>, Vec<usize>)> {
+    (0..CARDS)
+        .map(|_| (sample(rng, max, winning).into_vec(), sample(rng, max, held).into_vec()))
+        .collect()
+}
+
+fn benchmark(name: &str, cards: &[(Vec<usize>, Vec<usize>)]) {
+    let start = Instant::now();
+    let expected = cards
+        .iter()
+        .map(|(winning, held)| winning.iter().filter(|x| held.contains(x)).count())
+        .collect_vec();
+    let vec_time = start.elapsed();
+
+    let start = Instant::now();
+    let result = cards
+        .iter()
+        .map(|(winning, held)| NumberSet::common_count(winning, held))
+        .collect_vec();
+    let set_time = start.elapsed();
+
+    assert!(result == expected);
+    println!("{:<28} Vec::contains: {:>10.2?}, NumberSet: {:>10.2?}", name, vec_time, set_time);
+}
+
+fn main() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(4);
+
+    benchmark("10 of 25, numbers < 100", &random_cards(&mut rng, 10, 25, 100));
+    benchmark("50 of 100, numbers < 128", &random_cards(&mut rng, 50, 100, 128));
+    // mostly above 128, dense enough for the large bitset
+    benchmark("50 of 100, numbers < 10000", &random_cards(&mut rng, 50, 100, 10_000));
+    // too sparse for a bitset, kept as sorted lists
+    benchmark("50 of 100, numbers < 10^9", &random_cards(&mut rng, 50, 100, 1_000_000_000));
+    // long lists, built into sets
+    benchmark("200 of 500, numbers < 10000", &random_cards(&mut rng, 200, 500, 10_000));
+    benchmark("200 of 500, numbers < 10^9", &random_cards(&mut rng, 200, 500, 1_000_000_000));
+}
@@ -0,0 +1,169 @@
+//! Multi-pattern string search in a single pass over the text.
+//! https://en.wikipedia.org/wiki/Aho%E2%80%93Corasick_algorithm
+
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Match {
+    // index of the pattern
+    pub pattern: usize,
+    // byte range of the match, end exclusive
+    pub start: usize,
+    pub end: usize
+}
+
+/// Deterministic automaton, every state has a transition for every byte
+struct Automaton {
+    transitions: Vec<[u32; 256]>,
+    // patterns ending in each state, including the ones reachable by suffix links
+    outputs: Vec<Vec<usize>>,
+    pattern_lengths: Vec<usize>
+}
+
+impl Automaton {
+    const ROOT: u32 = 0;
+
+    fn new(patterns: &[Vec<u8>]) -> Self {
+        // trie, 0 marks a missing transition (the root is never a target in the trie)
+        let mut transitions = vec![[Self::ROOT; 256]];
+        let mut outputs = vec![Vec::new()];
+
+        for (idx, pattern) in patterns.iter().enumerate() {
+            assert!(!pattern.is_empty(), "Patterns should not be empty");
+            let mut state = Self::ROOT;
+            for &byte in pattern {
+                if transitions[state as usize][byte as usize] == Self::ROOT {
+                    transitions.push([Self::ROOT; 256]);
+                    outputs.push(Vec::new());
+                    transitions[state as usize][byte as usize] = (transitions.len() - 1) as u32;
+                }
+                state = transitions[state as usize][byte as usize];
+            }
+            outputs[state as usize].push(idx);
+        }
+
+        // breadth first, so the suffix link of a state is complete before its children
+        let mut suffix_links = vec![Self::ROOT; transitions.len()];
+        let mut queue: VecDeque<u32> = transitions[Self::ROOT as usize]
+            .iter()
+            .cloned()
+            .filter(|&child| child != Self::ROOT)
+            .collect();
+
+        while let Some(state) = queue.pop_front() {
+            let link = suffix_links[state as usize];
+            let inherited = outputs[link as usize].clone();
+            outputs[state as usize].extend(inherited);
+
+            let link_transitions = transitions[link as usize];
+            for (child, &fallback) in transitions[state as usize].iter_mut().zip(link_transitions.iter()) {
+                if *child == Self::ROOT {
+                    *child = fallback;
+                } else {
+                    suffix_links[*child as usize] = fallback;
+                    queue.push_back(*child);
+                }
+            }
+        }
+
+        Automaton {
+            transitions,
+            outputs,
+            pattern_lengths: patterns.iter().map(Vec::len).collect()
+        }
+    }
+
+    /// Matches in the bytes, positions count from the first byte given
+    fn find_overlapping<'a>(&'a self, bytes: impl Iterator<Item = u8> + 'a) -> impl Iterator<Item = Match> + 'a {
+        bytes
+            .enumerate()
+            .scan(Self::ROOT, |state, (pos, byte)| {
+                *state = self.transitions[*state as usize][byte as usize];
+                Some((pos + 1, *state))
+            })
+            .flat_map(move |(end, state)| {
+                self.outputs[state as usize]
+                    .iter()
+                    .map(move |&pattern| Match { pattern, start: end - self.pattern_lengths[pattern], end })
+            })
+    }
+
+    /// The match with the leftmost start, the longest one if there are more
+    fn find_leftmost(&self, bytes: impl Iterator<Item = u8>) -> Option<Match> {
+        let longest_pattern = self.pattern_lengths.iter().max().cloned().unwrap_or(0);
+        let mut best: Option<Match> = None;
+
+        for m in self.find_overlapping(bytes) {
+            // no later match can start before the best one
+            if best.is_some_and(|best| m.end > best.start + longest_pattern) {
+                break;
+            }
+            if best.is_none_or(|best| (m.start, best.end) < (best.start, m.end)) {
+                best = Some(m);
+            }
+        }
+
+        best
+    }
+}
+
+/// Finds occurrences of many patterns at once, matches may overlap
+pub struct AhoCorasick {
+    forward: Automaton,
+    // for the reversed patterns, to search from the end of the text
+    backward: Automaton
+}
+
+impl AhoCorasick {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        let patterns: Vec<Vec<u8>> = patterns.iter().map(|p| p.as_ref().to_vec()).collect();
+        let reversed: Vec<Vec<u8>> = patterns.iter().map(|p| p.iter().rev().cloned().collect()).collect();
+        AhoCorasick {
+            forward: Automaton::new(&patterns),
+            backward: Automaton::new(&reversed)
+        }
+    }
+
+    /// All matches, ordered by their end
+    pub fn find_overlapping<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        self.forward.find_overlapping(haystack.iter().cloned())
+    }
+
+    /// The match starting first, the longest one if more start at the same position
+    pub fn find_leftmost(&self, haystack: &[u8]) -> Option<Match> {
+        self.forward.find_leftmost(haystack.iter().cloned())
+    }
+
+    /// The match ending last, the longest one if more end at the same position.
+    /// Searches from the end, so it does not look at the whole text.
+    pub fn find_rightmost(&self, haystack: &[u8]) -> Option<Match> {
+        self.backward
+            .find_leftmost(haystack.iter().rev().cloned())
+            .map(|m| Match { pattern: m.pattern, start: haystack.len() - m.end, end: haystack.len() - m.start })
+    }
+}
+
+#[test]
+fn aho_corasick_overlapping() {
+    let matcher = AhoCorasick::new(&["he", "she", "his", "hers"]);
+    let matches: Vec<_> = matcher
+        .find_overlapping(b"ushers")
+        .map(|m| (m.pattern, m.start, m.end))
+        .collect();
+    assert!(matches == [(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+
+    let matcher = AhoCorasick::new(&["eight", "two"]);
+    let matches: Vec<_> = matcher.find_overlapping(b"eightwo").map(|m| m.pattern).collect();
+    assert!(matches == [0, 1]);
+}
+
+#[test]
+fn aho_corasick_leftmost_rightmost() {
+    let matcher = AhoCorasick::new(&["bcd", "abcdef", "c", "ef", "f"]);
+    assert!(matcher.find_leftmost(b"xabcdefx") == Some(Match { pattern: 1, start: 1, end: 7 }));
+    assert!(matcher.find_leftmost(b"xbcdefx") == Some(Match { pattern: 0, start: 1, end: 4 }));
+    assert!(matcher.find_rightmost(b"xabcdefx") == Some(Match { pattern: 1, start: 1, end: 7 }));
+    assert!(matcher.find_rightmost(b"xbcdefx") == Some(Match { pattern: 3, start: 4, end: 6 }));
+    assert!(matcher.find_leftmost(b"xyz").is_none());
+    assert!(matcher.find_rightmost(b"").is_none());
+}
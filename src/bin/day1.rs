@@ -1,61 +1,270 @@
 use aoc2023_rust::*;
-use itertools::{Itertools, MinMaxResult};
+use aoc2023_rust::aho_corasick::AhoCorasick;
+use thiserror::Error;
 
 /// Given a line, find the first and the last digit in the line.
-/// Return 10 * d1 + d2
-fn process_line_part1(line: &str) -> u32 {
+/// Return 10 * d1 + d2, or None if there is no digit
+fn process_line_part1(line: &str) -> Option<u32> {
     let digits: Vec<_> = line.chars().filter_map(|c| c.to_digit(10)).collect();
-    let (d1, d2) = (digits.first().unwrap(), digits.last().unwrap());
-    10 * d1 + d2
+    let (d1, d2) = (digits.first()?, digits.last()?);
+    Some(10 * d1 + d2)
 }
 
-/// Given a line, find the first and the last digit in the line - written as a number or a word.
-/// Returns 10 * d1 + d2
-fn process_line_part2(line: &str) -> u32 {
-    const DIGITS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
-    const DIGITS2: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+/// Languages with number words
+#[derive(Clone, Copy)]
+enum Language {
+    English,
+    Polish,
+    German
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Language::English),
+            "pl" => Ok(Language::Polish),
+            "de" => Ok(Language::German),
+            _ => Err(format!("Unknown language \"{}\", expected en, pl or de", s))
+        }
+    }
+}
 
-    let mut all_matches = Vec::new();
-    let digit_patterns = (1..=9).zip(DIGITS).chain((1..=9).zip(DIGITS2));
+impl Language {
+    /// All the ways to write `n`, for 0 <= n < 100, in lowercase
+    fn spell(self, n: u32) -> Vec<String> {
+        const EN_UNITS: [&str; 10] = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+        const EN_TEENS: [&str; 10] = ["ten", "eleven", "twelve", "thirteen", "fourteen",
+                                      "fifteen", "sixteen", "seventeen", "eighteen", "nineteen"];
+        const EN_TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
 
-    for (digit, name) in digit_patterns {
-        let digit_matches = line.match_indices(name).map(|pos| (pos.0, digit));
-        all_matches.extend(digit_matches);
+        const PL_UNITS: [&str; 10] = ["zero", "jeden", "dwa", "trzy", "cztery", "pięć", "sześć", "siedem", "osiem", "dziewięć"];
+        const PL_TEENS: [&str; 10] = ["dziesięć", "jedenaście", "dwanaście", "trzynaście", "czternaście",
+                                      "piętnaście", "szesnaście", "siedemnaście", "osiemnaście", "dziewiętnaście"];
+        const PL_TENS: [&str; 10] = ["", "", "dwadzieścia", "trzydzieści", "czterdzieści", "pięćdziesiąt",
+                                     "sześćdziesiąt", "siedemdziesiąt", "osiemdziesiąt", "dziewięćdziesiąt"];
+
+        const DE_UNITS: [&str; 10] = ["null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"];
+        const DE_TEENS: [&str; 10] = ["zehn", "elf", "zwölf", "dreizehn", "vierzehn",
+                                      "fünfzehn", "sechzehn", "siebzehn", "achtzehn", "neunzehn"];
+        const DE_TENS: [&str; 10] = ["", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig"];
+
+        let (units, teens, tens) = match self {
+            Language::English => (EN_UNITS, EN_TEENS, EN_TENS),
+            Language::Polish => (PL_UNITS, PL_TEENS, PL_TENS),
+            Language::German => (DE_UNITS, DE_TEENS, DE_TENS)
+        };
+        let (ten, unit) = (n as usize / 10, n as usize % 10);
+
+        match (ten, unit) {
+            (0, _) => vec![units[unit].to_string()],
+            (1, _) => vec![teens[unit].to_string()],
+            (_, 0) => vec![tens[ten].to_string()],
+            // "twenty one", "twenty-one", "twentyone"
+            _ => match self {
+                Language::English => ["", " ", "-"].iter().map(|sep| format!("{}{}{}", tens[ten], sep, units[unit])).collect(),
+                Language::Polish => ["", " "].iter().map(|sep| format!("{}{}{}", tens[ten], sep, units[unit])).collect(),
+                // "einundzwanzig", the unit goes first and "eins" becomes "ein"
+                Language::German => vec![format!("{}und{}", if unit == 1 { "ein" } else { units[unit] }, tens[ten])]
+            }
+        }
     }
+}
+
+/// Number words to look for in the lines, besides the digits.
+/// The first number found gives its leading digit, the last one its trailing digit.
+struct NumberWords {
+    // value of each pattern in the matcher
+    values: Vec<u32>,
+    matcher: AhoCorasick
+}
+
+impl NumberWords {
+    /// Words for one to nine, and zero if `with_zero`.
+    /// `with_compounds` adds the numbers from ten to ninety-nine.
+    fn new(language: Language, with_zero: bool, with_compounds: bool) -> Self {
+        let first = if with_zero { 0 } else { 1 };
+        let last = if with_compounds { 99 } else { 9 };
+
+        let mut patterns = Vec::new();
+        let mut values = Vec::new();
+        for n in first..=last {
+            let words = language.spell(n);
+            let digits = (n < 10).then(|| n.to_string());
+            for pattern in words.into_iter().chain(digits) {
+                patterns.push(pattern);
+                values.push(n);
+            }
+        }
+
+        NumberWords { values, matcher: AhoCorasick::new(&patterns) }
+    }
+}
+
+impl Default for NumberWords {
+    /// English words for one to nine, as in the puzzle
+    fn default() -> Self {
+        NumberWords::new(Language::English, false, false)
+    }
+}
+
+/// Given a line, find the first and the last digit in the line - written as a number or a word.
+/// Words are matched regardless of case.
+/// Returns 10 * d1 + d2, or None if there is no digit
+fn process_line_part2(line: &str, numbers: &NumberWords) -> Option<u32> {
+    let line = line.to_lowercase();
 
     // get digits from the first and last position
-    let first_last = all_matches.iter().minmax_by_key(|(pos, _)| pos);
-    match first_last {
-        MinMaxResult::NoElements => panic!("Each line must contain a digit"),
-        MinMaxResult::OneElement((_, x)) => 10*x + x,
-        MinMaxResult::MinMax((_, x), (_, y)) => 10*x + y
+    let first = numbers.matcher.find_leftmost(line.as_bytes())?;
+    let last = numbers.matcher.find_rightmost(line.as_bytes())?;
+
+    let mut leading_digit = numbers.values[first.pattern];
+    while leading_digit >= 10 {
+        leading_digit /= 10;
     }
+    Some(10 * leading_digit + numbers.values[last.pattern] % 10)
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Line {line_number} has no digit: \"{line}\"")]
+struct DigitlessLine {
+    // counted from 1
+    line_number: usize,
+    line: String
+}
+
+/// What to do with the lines without digits
+#[derive(Clone, Copy)]
+enum DigitlessMode {
+    /// Fail on the first one
+    Strict,
+    /// Leave them out of the sum
+    Skip,
+    /// Count them as 0
+    Zero
+}
+
+#[derive(PartialEq, Debug)]
+struct Calibration {
+    sum: u32,
+    // lines contributing to the sum
+    lines: usize,
+    digitless: Vec<DigitlessLine>
+}
+
+/// Sums the calibration values of the lines, handling the ones without digits according to `mode`
+fn calibrate<'a>(lines: impl Iterator<Item = &'a str>, process_line: impl Fn(&str) -> Option<u32>, mode: DigitlessMode)
+        -> Result<Calibration, DigitlessLine> {
+    let mut calibration = Calibration { sum: 0, lines: 0, digitless: Vec::new() };
+    for (idx, line) in lines.enumerate() {
+        match (process_line(line), mode) {
+            (Some(value), _) => {
+                calibration.sum += value;
+                calibration.lines += 1;
+            }
+            (None, DigitlessMode::Strict) => return Err(DigitlessLine { line_number: idx + 1, line: line.to_string() }),
+            (None, _) => {
+                if let DigitlessMode::Zero = mode {
+                    calibration.lines += 1;
+                }
+                calibration.digitless.push(DigitlessLine { line_number: idx + 1, line: line.to_string() });
+            }
+        }
+    }
+    Ok(calibration)
 }
 
 fn main() {
-    let lines = read_input_lines("input/day1.txt");
-    let part1: u32 = lines.iter().map(|s| process_line_part1(s)).sum();
-    println!("Part 1: {}", part1);
+    // `--lang en|pl|de` selects the language of the number words,
+    // `--zero` adds zero, `--compounds` adds the numbers up to ninety-nine,
+    // `--digitless skip|zero` reports the lines without digits instead of failing on them
+    let args: Vec<String> = std::env::args().collect();
+    let has_flag = |name: &str| args.iter().any(|arg| arg == name);
 
-    let part2: u32 = lines.iter().map(|s| process_line_part2(s)).sum();
-    println!("Part 2: {}", part2);
+    let language = flag_value(&args, "--lang").map_or(Ok(Language::English), |lang| lang.parse()).unwrap();
+    let numbers = NumberWords::new(language, has_flag("--zero"), has_flag("--compounds"));
+    let mode = match flag_value(&args, "--digitless") {
+        None | Some("strict") => DigitlessMode::Strict,
+        Some("skip") => DigitlessMode::Skip,
+        Some("zero") => DigitlessMode::Zero,
+        Some(other) => panic!("Unknown mode \"{}\", expected strict, skip or zero", other)
+    };
+
+    let input = read_input("input/day1.txt");
+    let report = |part: u32, calibration: Result<Calibration, DigitlessLine>| match calibration {
+        Ok(calibration) => {
+            for digitless in &calibration.digitless {
+                eprintln!("{}", digitless);
+            }
+            println!("Part {}: {}", part, calibration.sum);
+        }
+        Err(digitless) => eprintln!("Part {}: {}", part, digitless)
+    };
+
+    report(1, calibrate(input.lines(), process_line_part1, mode));
+    report(2, calibrate(input.lines(), |line| process_line_part2(line, &numbers), mode));
 }
 
 #[test]
 fn day1_part1_example() {
-    assert!(process_line_part1("1abc2")       == 12);
-    assert!(process_line_part1("pqr3stu8vwx") == 38);
-    assert!(process_line_part1("a1b2c3d4e5f") == 15);
-    assert!(process_line_part1("treb7uchet")  == 77);
+    assert!(process_line_part1("1abc2")       == Some(12));
+    assert!(process_line_part1("pqr3stu8vwx") == Some(38));
+    assert!(process_line_part1("a1b2c3d4e5f") == Some(15));
+    assert!(process_line_part1("treb7uchet")  == Some(77));
 }
 
 #[test]
 fn day1_part2_example() {
-    assert!(process_line_part2("two1nine")          == 29);
-    assert!(process_line_part2("eightwothree")      == 83);
-    assert!(process_line_part2("abcone2threexyz")   == 13);
-    assert!(process_line_part2("xtwone3four")       == 24);
-    assert!(process_line_part2("4nineeightseven2")  == 42);
-    assert!(process_line_part2("zoneight234")       == 14);
-    assert!(process_line_part2("7pqrstsixteen")     == 76);
+    let english = NumberWords::default();
+    assert!(process_line_part2("two1nine", &english)          == Some(29));
+    assert!(process_line_part2("eightwothree", &english)      == Some(83));
+    assert!(process_line_part2("abcone2threexyz", &english)   == Some(13));
+    assert!(process_line_part2("xtwone3four", &english)       == Some(24));
+    assert!(process_line_part2("4nineeightseven2", &english)  == Some(42));
+    assert!(process_line_part2("zoneight234", &english)       == Some(14));
+    assert!(process_line_part2("7pqrstsixteen", &english)     == Some(76));
+}
+
+#[test]
+fn day1_number_words() {
+    let polish = NumberWords::new(Language::Polish, false, false);
+    assert!(process_line_part2("xjedenaściedwa", &polish) == Some(12));
+    assert!(process_line_part2("Osiem3Dziewięć", &polish) == Some(89));
+
+    let german = NumberWords::new(Language::German, true, false);
+    assert!(process_line_part2("NullacHt", &german) == Some(8));
+    assert!(process_line_part2("zweinsx", &german) == Some(21));
+
+    let english = NumberWords::new(Language::English, true, true);
+    assert!(process_line_part2("twenty one", &english) == Some(21));
+    assert!(process_line_part2("thirty-seven and four", &english) == Some(34));
+    assert!(process_line_part2("Sixteen", &english) == Some(16));
+    assert!(process_line_part2("zero2forty", &english) == Some(0));
+
+    let german = NumberWords::new(Language::German, false, true);
+    assert!(process_line_part2("dreiundzwanzigfünfzehn", &german) == Some(25));
+    assert!(process_line_part2("SechsundzwanzigX", &german) == Some(26));
+    assert!(process_line_part2("einundneunzig", &german) == Some(91));
+}
+
+#[test]
+fn day1_digitless_lines() {
+    let lines = ["1abc2", "abc", "", "treb7uchet"];
+    assert!(process_line_part1("abc").is_none());
+    assert!(process_line_part2("abc", &NumberWords::default()).is_none());
+
+    let first_digitless = DigitlessLine { line_number: 2, line: "abc".to_string() };
+    let strict = calibrate(lines.into_iter(), process_line_part1, DigitlessMode::Strict);
+    assert!(strict == Err(first_digitless));
+
+    let skip = calibrate(lines.into_iter(), process_line_part1, DigitlessMode::Skip).unwrap();
+    assert!(skip.sum == 12 + 77);
+    assert!(skip.lines == 2);
+    assert!(skip.digitless.iter().map(|line| line.line_number).collect::<Vec<_>>() == [2, 3]);
+
+    let zero = calibrate(lines.into_iter(), process_line_part1, DigitlessMode::Zero).unwrap();
+    assert!(zero.sum == 12 + 77);
+    assert!(zero.lines == 4);
+    assert!(zero.digitless == skip.digitless);
 }
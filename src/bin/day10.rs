@@ -1,84 +1,162 @@
+use aoc2023_rust::geometry::LatticePolygon;
+use aoc2023_rust::pipes::{enclosed_tiles, print_pipe_board, Connections};
 use itertools::Itertools;
 use ndarray::{Array2, ArrayView2, ArrayView1};
 use simple_scan::IteratorSimpleScanExt;
 
-struct Neighbours {
-    up:    [usize; 2],
-    down:  [usize; 2],
-    left:  [usize; 2],
-    right: [usize; 2],
+/// Positions the pipe connects to, None if there is no pipe at the position
+fn pipe_connections(board: ArrayView2<u8>, pipe_position: [usize; 2])
+        -> Option<([usize; 2], [usize; 2])> {
+    let connections = Connections::from_tile(*board.get(pipe_position)?)?;
+    let (d1, d2) = connections.iter().collect_tuple()?;
+    Some((d1.neighbour(pipe_position), d2.neighbour(pipe_position)))
 }
 
-fn neighbours_of([y, x]: [usize; 2]) -> Neighbours {
-    Neighbours {
-        up:    [y.wrapping_sub(1), x], // [y+1, x  ]
-        down:  [y.wrapping_add(1), x], // [y-1, x  ]
-        left:  [y, x.wrapping_sub(1)], // [y,   x-1]
-        right: [y, x.wrapping_add(1)], // [y,   x+1]
-    }
+fn connections_to_pipe(position: [usize; 2], c1: [usize; 2], c2: [usize; 2]) -> u8 {
+    let direction = |c| Connections::direction_to(position, c)
+        .expect("The two positions should be adjacent to the position");
+    (direction(c1) | direction(c2))
+        .to_tile()
+        .expect("The two positions should be different")
 }
 
-fn pipe_connections(board: ArrayView2<u8>, pipe_position: [usize; 2])
-        -> ([usize; 2], [usize; 2]) {
-    let Neighbours { up, down, left, right } = neighbours_of(pipe_position);
-
-    match board.get(pipe_position).unwrap() {
-        b'|' => (up, down),
-        b'-' => (left, right),
-        b'L' => (up, right),
-        b'J' => (up, left),
-        b'7' => (left, down),
-        b'F' => (down, right),
-        _ => panic!("Unexpeted non-pipe character")
+fn take_the_other<T: PartialEq>(pair: (T, T), element: T) -> Option<T> {
+    if pair.0 == element { Some(pair.1) }
+    else if pair.1 == element { Some(pair.0) }
+    else { None }
+}
+
+/// Positions visited by following the pipes
+struct PipeWalk {
+    // starts with the starting position
+    positions: Vec<[usize; 2]>,
+    // did the walk get back to the starting position
+    closed: bool
+}
+
+/// Follows the pipes from `start` through `first`, until it gets back to `start`
+/// or finds a pipe that does not connect to the previous one.
+fn follow_pipes(board: ArrayView2<u8>, start: [usize; 2], first: [usize; 2]) -> PipeWalk {
+    let mut positions = vec![start];
+    let (mut prev, mut curr) = (start, first);
+
+    while curr != start {
+        let next = pipe_connections(board, curr)
+            .and_then(|connections| take_the_other(connections, prev));
+        let Some(next) = next else {
+            return PipeWalk { positions, closed: false };
+        };
+        positions.push(curr);
+        (prev, curr) = (curr, next);
     }
+
+    PipeWalk { positions, closed: true }
 }
 
-fn connections_to_pipe(c1: [usize; 2], c2: [usize; 2]) -> u8 {
-    let [y, x] = [(c1[0] + c2[0]) / 2, (c1[1] + c2[1]) / 2];
-    let Neighbours {up, down, left, right} = neighbours_of([y, x]);
+/// Pipes connected to the position, that connect back to it
+fn connecting_neighbours(board: ArrayView2<u8>, position: [usize; 2]) -> Vec<[usize; 2]> {
+    Connections::all()
+        .iter()
+        .map(|direction| direction.neighbour(position))
+        .filter(|pos| {
+            pipe_connections(board, *pos).is_some_and(|(p1, p2)| p1 == position || p2 == position)
+        })
+        .collect()
+}
 
-    if      [c1, c2] == [up, down]    || [c1, c2] == [down, up]    { b'|' }
-    else if [c1, c2] == [up, left]    || [c1, c2] == [left, up]    { b'J' }
-    else if [c1, c2] == [up, right]   || [c1, c2] == [right, up]   { b'L' }
-    else if [c1, c2] == [down, left]  || [c1, c2] == [left, down]  { b'7' }
-    else if [c1, c2] == [down, right] || [c1, c2] == [right, down] { b'F' }
-    else if [c1, c2] == [left, right] || [c1, c2] == [right, left] { b'-' }
-    else { panic!("The two positions should be adjacent to the same block") }
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum PipeDefect {
+    // one of the ends leads outside of the board or to the ground
+    Dangling,
+    // one of the ends leads to a pipe that does not connect back
+    Mismatched
 }
 
-fn take_the_other<T: PartialEq>(pair: (T, T), element: T) -> T {
-    if pair.0 == element { pair. 1 }
-    else if pair.1 == element { pair. 0 }
-    else { panic!("Element was not part of the pair") }
+/// A loop going through the start
+struct StartLoop {
+    // pipe that S has to be for this loop
+    start_pipe: u8,
+    // positions of the loop, starting with S
+    positions: Vec<[usize; 2]>
 }
 
-fn next_pipe_position(board: ArrayView2<u8>,
-                      curr_pipe: [usize; 2],
-                      prev_pipe: [usize; 2]) -> [usize; 2] {
-    let connections = pipe_connections(board, curr_pipe);
-    take_the_other(connections, prev_pipe)
+struct NetworkReport {
+    start_loops: Vec<StartLoop>,
+    // closed loops not going through the start, each listed once
+    other_loops: Vec<Vec<[usize; 2]>>,
+    defects: Vec<([usize; 2], PipeDefect)>
 }
 
-fn get_starting_pipes(board: ArrayView2<u8>, start: [usize; 2])
-        -> [[usize; 2]; 2] {
-    assert!(board[start] == b'S');
+/// Finds all the loops on the board and the pipes that cannot be part of any loop.
+/// S may connect to more than two pipes, every pair of them is tried as a loop.
+fn analyse_network(board: ArrayView2<u8>, start: [usize; 2]) -> NetworkReport {
+    let mut board = board.to_owned();
 
-    let Neighbours {up, down, left, right} = neighbours_of(start);
+    let defects = board
+        .indexed_iter()
+        .filter_map(|((y, x), _)| {
+            let (c1, c2) = pipe_connections(board.view(), [y, x])?;
+            // dangling end is reported over a mismatched one
+            let defect = [c1, c2].into_iter().filter_map(|c| {
+                match (board.get(c), pipe_connections(board.view(), c)) {
+                    (Some(b'S'), _) => None,
+                    (_, None) => Some(PipeDefect::Dangling),
+                    (_, Some((p1, p2))) if p1 != [y, x] && p2 != [y, x] => Some(PipeDefect::Mismatched),
+                    _ => None
+                }
+            }).min();
+            defect.map(|defect| ([y, x], defect))
+        })
+        .collect_vec();
 
-    let connected_positions: Vec<_> = [up, down, left, right]
+    let start_loops = connecting_neighbours(board.view(), start)
         .into_iter()
-        .filter(|pos| {
-            // skip indices outside of the board, skip non-pipes
-            board.get(*pos).is_some_and(|field| *field != b'.')
+        .tuple_combinations()
+        .filter_map(|(c1, c2)| {
+            let start_pipe = connections_to_pipe(start, c1, c2);
+            board[start] = start_pipe;
+            let walk = follow_pipes(board.view(), start, c1);
+            (walk.closed && walk.positions.last() == Some(&c2))
+                .then_some(StartLoop { start_pipe, positions: walk.positions })
         })
-        .filter(|pos| {
-            // take the pipes that connect to the start
-            let (p1, p2) = pipe_connections(board.view(), *pos);
-            p1 == start || p2 == start
-        })
-        .collect();
+        .collect_vec();
 
-    connected_positions.try_into().unwrap()
+    // the start is not a pipe when looking for other loops
+    board[start] = b'.';
+    let mut visited = Array2::from_elem(board.raw_dim(), false);
+    let mut other_loops = Vec::new();
+
+    for ((y, x), _) in board.indexed_iter() {
+        if visited[[y, x]] { continue; }
+        let Some((c1, c2)) = pipe_connections(board.view(), [y, x]) else { continue; };
+
+        // every pipe on the walk belongs to the same loop or chain of pipes
+        let walk = follow_pipes(board.view(), [y, x], c1);
+        walk.positions.iter().for_each(|&pos| visited[pos] = true);
+        if walk.closed && walk.positions.last() == Some(&c2) {
+            other_loops.push(walk.positions);
+        }
+    }
+
+    NetworkReport { start_loops, other_loops, defects }
+}
+
+impl std::fmt::Display for NetworkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Loops through S: {}", self.start_loops.len())?;
+        for start_loop in &self.start_loops {
+            writeln!(f, "  S as '{}', length {}", start_loop.start_pipe as char, start_loop.positions.len())?;
+        }
+
+        writeln!(f, "Other loops: {}", self.other_loops.len())?;
+        for other_loop in &self.other_loops {
+            writeln!(f, "  at {:?}, length {}", other_loop[0], other_loop.len())?;
+        }
+
+        let defect_counts = self.defects.iter().map(|(_, defect)| defect).counts();
+        writeln!(f, "Dangling pipes: {}", defect_counts.get(&PipeDefect::Dangling).unwrap_or(&0))?;
+        writeln!(f, "Mismatched pipes: {}", defect_counts.get(&PipeDefect::Mismatched).unwrap_or(&0))
+    }
 }
 
 fn scan_board_line_for_inner_part(line: ArrayView1<u8>) -> usize {
@@ -126,55 +204,203 @@ fn scan_board_line_for_inner_part(line: ArrayView1<u8>) -> usize {
         .count()
 }
 
-fn main() {
-    let input = aoc2023_rust::read_input_lines("input/day10.txt");
-    let width = input[0].len();
-    let height = input.len();
-    let input = input.join("").into_bytes();
+/// Number of tiles enclosed by the loop, treating the loop as a polygon.
+/// Every tile of the loop is a boundary point, the enclosed tiles are the interior points.
+fn enclosed_tiles_by_area(loop_positions: &[[usize; 2]]) -> usize {
+    let vertices = loop_positions
+        .iter()
+        .map(|&[y, x]| [y as i64, x as i64])
+        .collect();
+    LatticePolygon::new(vertices).interior_points() as usize
+}
+
+/// The board with only the loop on it, S replaced with its pipe
+fn loop_board(board: ArrayView2<u8>, start_loop: &StartLoop) -> Array2<u8> {
+    let mut loop_board = Array2::from_elem(board.raw_dim(), b'.');
+    for &position in &start_loop.positions {
+        loop_board[position] = board[position];
+    }
+    loop_board[start_loop.positions[0]] = start_loop.start_pipe;
+    loop_board
+}
 
+fn enclosed_by_ray_casting(loop_board: ArrayView2<u8>) -> usize {
+    loop_board
+        .rows()
+        .into_iter()
+        .map(scan_board_line_for_inner_part)
+        .sum()
+}
+
+fn main() {
     // 2D board with pipes
-    let mut board = Array2::from_shape_vec((height, width), input)
-        .expect("Board dimensions should match");
-    // 2D board with boolean flags, is this field part of the loop we are looking for?
-    let mut board_is_loop = Array2::from_elem(board.raw_dim(), false);
+    let board = aoc2023_rust::read_2d_board("input/day10.txt");
 
     // Position of S on the board
-    let start: [usize; 2] = board.indexed_iter().find(|(_, c)| **c == b'S' ).unwrap().0.into();
-
-    // Pipes connected to S
-    let start_pipes = get_starting_pipes(board.view(), start);
-    board[start] = connections_to_pipe(start_pipes[0], start_pipes[1]);
-
-    let move_to_next_pipe =
-        |(prev, curr)|
-            (
-                curr,
-                next_pipe_position(board.view(), curr, prev)
-            );
-
-    board_is_loop[start] = true;
-    let mut counter = 1;
-    let mut direction = (start, start_pipes[0]);
-
-    while direction.1 != start {
-        board_is_loop[direction.1] = true;
-        direction = move_to_next_pipe(direction);
-        counter += 1;
+    let Some((start, _)) = board.indexed_iter().find(|(_, c)| **c == b'S') else {
+        eprintln!("There is no S on the board");
+        return;
+    };
+    let start: [usize; 2] = start.into();
+
+    let report = analyse_network(board.view(), start);
+    if std::env::args().any(|arg| arg == "--report") {
+        print!("{}", report);
     }
 
-    println!("Part 1: {}", counter / 2);
+    // If S could close multiple loops, the longest one is taken
+    let Some(start_loop) = report.start_loops.iter().max_by_key(|l| l.positions.len()) else {
+        eprintln!("No loop goes through S");
+        return;
+    };
+    let loop_positions = &start_loop.positions;
 
-    // Replace all elements that are not part of the loop with b'.'
-    ndarray::Zip::from(&mut board)
-        .and(&board_is_loop)
-        .for_each(|board_field, is_loop|
-            if !*is_loop { *board_field = b'.' } );
+    println!("Part 1: {}", loop_positions.len() / 2);
 
-    let part2: usize = board
-        .rows()
-        .into_iter()
-        .map(scan_board_line_for_inner_part)
-        .sum();
+    // all elements that are not part of the loop are b'.'
+    let mut board = loop_board(board.view(), start_loop);
+    let part2 = enclosed_by_ray_casting(board.view());
+
+    // cross-check with independent methods, a disagreement is reported next to the answer
+    let part2_by_area = enclosed_tiles_by_area(loop_positions);
+    let enclosed = enclosed_tiles(board.view(), 2);
+    let part2_by_flood_fill = enclosed.iter().filter(|&&inside| inside).count();
+    if part2 != part2_by_area || part2 != part2_by_flood_fill {
+        eprintln!("Enclosed tiles disagree: ray casting {}, Pick's theorem {}, flood fill {}",
+                  part2, part2_by_area, part2_by_flood_fill);
+    }
+
+    if std::env::args().any(|arg| arg == "--draw") {
+        // enclosed tiles marked with 'I'
+        ndarray::Zip::from(&mut board)
+            .and(&enclosed)
+            .for_each(|board_field, inside|
+                if *inside { *board_field = b'I' } );
+        print_pipe_board(board.view());
+    }
 
     println!("Part 2: {}", part2);
 }
+
+#[cfg(test)]
+fn board_from(lines: &[&str]) -> Array2<u8> {
+    aoc2023_rust::InputBuffer::new(lines.join("\n")).to_2d_board()
+}
+
+#[test]
+fn day10_network_single_loop() {
+    let board = board_from(&[
+        "-L|F7",
+        "7S-7|",
+        "L|7||",
+        "-L-J|",
+        "L|-JF",
+    ]);
+    let report = analyse_network(board.view(), [1, 1]);
+    assert!(report.start_loops.len() == 1);
+    assert!(report.start_loops[0].start_pipe == b'F');
+    assert!(report.start_loops[0].positions.len() == 8);
+    assert!(report.other_loops.is_empty());
+    assert!(report.defects.contains(&([0, 0], PipeDefect::Dangling)));
+    assert!(report.defects.contains(&([2, 2], PipeDefect::Mismatched)));
+}
+
+#[test]
+fn day10_network_start_with_four_neighbours() {
+    let board = board_from(&[
+        "F-7..F7",
+        "|.|..LJ",
+        "L-S-7..",
+        "..|.|..",
+        "..L-J..",
+    ]);
+    let report = analyse_network(board.view(), [2, 2]);
+    let start_pipes = report.start_loops.iter().map(|l| l.start_pipe).collect_vec();
+    assert!(start_pipes == [b'J', b'F']);
+    assert!(report.start_loops.iter().all(|l| l.positions.len() == 8));
+    assert!(report.other_loops.len() == 1);
+    assert!(report.other_loops[0].len() == 4);
+}
+
+#[test]
+fn day10_network_no_loop() {
+    let board = board_from(&[
+        "..F",
+        ".S-",
+        "...",
+    ]);
+    let report = analyse_network(board.view(), [1, 1]);
+    assert!(report.start_loops.is_empty());
+    assert!(report.defects == [([0, 2], PipeDefect::Dangling), ([1, 2], PipeDefect::Dangling)]);
+}
+
+#[test]
+fn day10_enclosed_methods_agree() {
+    let board = board_from(&[
+        ".F----7F7F7F7F-7....",
+        ".|F--7||||||||FJ....",
+        ".||.FJ||||||||L7....",
+        "FJL7L7LJLJ||LJ.L-7..",
+        "L--J.L7...LJS7F-7L7.",
+        "....F-J..F7FJ|L7L7L7",
+        "....L7.F7||L7|.L7L7|",
+        ".....|FJLJ|FJ|F7|.LJ",
+        "....FJL-7.||.||||...",
+        "....L---J.LJ.LJLJ...",
+    ]);
+    let report = analyse_network(board.view(), [4, 12]);
+    let start_loop = &report.start_loops[0];
+    let loop_board = loop_board(board.view(), start_loop);
+
+    let by_flood_fill = enclosed_tiles(loop_board.view(), 2).iter().filter(|&&inside| inside).count();
+    assert!(enclosed_by_ray_casting(loop_board.view()) == 8);
+    assert!(enclosed_tiles_by_area(&start_loop.positions) == 8);
+    assert!(by_flood_fill == 8);
+}
+
+/// Follows the loop from S on its own, and tells if each tile is inside by its winding number
+#[cfg(test)]
+mod reference {
+    use aoc2023_rust::pipes::loop_from_start;
+    use ndarray::ArrayView2;
+
+    pub fn enclosed_tiles(board: ArrayView2<u8>) -> usize {
+        let (start, _) = board.indexed_iter().find(|(_, &tile)| tile == b'S').unwrap();
+        let positions = loop_from_start(board, [start.0, start.1]).unwrap();
+
+        let winding_number = |[y, x]: [usize; 2]| -> i64 {
+            // vertical edges of the loop crossing the half-line going east from the tile,
+            // an edge covers its upper end but not the lower one
+            positions
+                .iter()
+                .zip(positions.iter().cycle().skip(1))
+                .filter(|(from, to)| from[1] == to[1] && from[1] > x && from[0].min(to[0]) == y)
+                .map(|(from, to)| if to[0] > from[0] { 1 } else { -1 })
+                .sum()
+        };
+
+        board
+            .indexed_iter()
+            .filter(|&((y, x), _)| !positions.contains(&[y, x]) && winding_number([y, x]) != 0)
+            .count()
+    }
+}
+
+#[test]
+fn day10_against_reference() {
+    use aoc2023_rust::differential::assert_agree;
+    use aoc2023_rust::generate::Day10;
+
+
+    let board = |text: &str| board_from(&text.lines().collect_vec());
+    let solve = |text: &str| {
+        let board = board(text);
+        let (start, _) = board.indexed_iter().find(|(_, &tile)| tile == b'S').unwrap();
+        let report = analyse_network(board.view(), start.into());
+        let start_loop = report.start_loops.iter().max_by_key(|l| l.positions.len()).unwrap();
+        enclosed_by_ray_casting(loop_board(board.view(), start_loop).view())
+    };
+
+    let generator = Day10 { width: 25, height: 18, tree_nodes: 20, junk: 0.6 };
+    assert_agree(generator, 100, |text| reference::enclosed_tiles(board(text).view()), solve);
+}
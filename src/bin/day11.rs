@@ -1,14 +1,48 @@
-use ndarray::{Array2, ArrayView1};
+use std::collections::HashMap;
 
-fn distances_after_expansion(line_counts: &[u64], expansion_rate: u64) -> u64 {
+use itertools::Itertools;
+use ndarray::{ArrayView1, ArrayView2};
+
+/// How many times the empty lines grow, separately for rows and columns.
+/// Custom rates override the default one for given empty lines.
+struct Expansion {
+    rows: u64,
+    columns: u64,
+    custom_rows: HashMap<usize, u64>,
+    custom_columns: HashMap<usize, u64>
+}
+
+impl Expansion {
+    fn uniform(expansion_rate: u64) -> Self {
+        Expansion {
+            rows: expansion_rate,
+            columns: expansion_rate,
+            custom_rows: HashMap::new(),
+            custom_columns: HashMap::new()
+        }
+    }
+}
+
+/// Sizes of the lines after expansion, only the lines without galaxies expand
+fn expanded_line_sizes(line_counts: &[u64], expansion_rate: u64, custom_rates: &HashMap<usize, u64>)
+        -> Vec<u64> {
+    line_counts
+        .iter()
+        .enumerate()
+        .map(|(idx, &count)|
+            if count == 0 { *custom_rates.get(&idx).unwrap_or(&expansion_rate) } else { 1 })
+        .collect()
+}
+
+fn distances_after_expansion(line_counts: &[u64], line_sizes: &[u64]) -> u64 {
     let mut acc = 0;
     let mut distance_to_prev_sum = 0;
     let mut count_prev = 0;
 
     // find distances each-to-each, line_counts describe number of elements
     // at given position.
-    // If == 0, this line expands according to expansion_rate
-    for &next_count in line_counts {
+    // line_sizes describe how far the line is from the next one
+    for (&next_count, &line_size) in line_counts.iter().zip(line_sizes) {
         // distances from the ones in the current line to the previous ones
         acc += next_count * distance_to_prev_sum;
 
@@ -16,37 +50,199 @@ fn distances_after_expansion(line_counts: &[u64], expansion_rate: u64) -> u64 {
         count_prev += next_count;
 
         // cumulative distance to all the previous ones
-        if next_count == 0 {
-            distance_to_prev_sum += expansion_rate * count_prev
-        } else {
-            distance_to_prev_sum += count_prev
-        }
+        distance_to_prev_sum += line_size * count_prev;
     }
 
     acc
 }
 
+/// Galaxies on the board
+struct Universe {
+    // [row, column] of each galaxy
+    galaxies: Vec<[usize; 2]>,
+    rows_counts: Vec<u64>,
+    cols_counts: Vec<u64>
+}
+
+impl Universe {
+    fn new(board: ArrayView2<u8>) -> Self {
+        let sum_line =
+            |line: ArrayView1<u8>| line.iter().filter(|&&c| c == b'#').count() as u64;
+
+        Universe {
+            galaxies: board
+                .indexed_iter()
+                .filter(|(_, &c)| c == b'#')
+                .map(|((y, x), _)| [y, x])
+                .collect(),
+            rows_counts: board.rows()   .into_iter().map(sum_line).collect(),
+            cols_counts: board.columns().into_iter().map(sum_line).collect()
+        }
+    }
+
+    fn line_sizes(&self, expansion: &Expansion) -> [Vec<u64>; 2] {
+        [
+            expanded_line_sizes(&self.rows_counts, expansion.rows, &expansion.custom_rows),
+            expanded_line_sizes(&self.cols_counts, expansion.columns, &expansion.custom_columns)
+        ]
+    }
+
+    /// Sum of distances between all pairs of galaxies
+    fn distances_sum(&self, expansion: &Expansion) -> u64 {
+        let [row_sizes, col_sizes] = self.line_sizes(expansion);
+        distances_after_expansion(&self.rows_counts, &row_sizes)
+            + distances_after_expansion(&self.cols_counts, &col_sizes)
+    }
+
+    /// [row, column] of each galaxy after expansion, in the order of `galaxies`
+    fn expanded_galaxies(&self, expansion: &Expansion) -> Vec<[u64; 2]> {
+        // expanded position of each line
+        let [row_positions, col_positions] = self
+            .line_sizes(expansion)
+            .map(|sizes| sizes.iter().scan(0, |pos, size| { *pos += size; Some(*pos - size) }).collect_vec());
+
+        self.galaxies
+            .iter()
+            .map(|&[y, x]| [row_positions[y], col_positions[x]])
+            .collect()
+    }
+
+    /// Distance between every pair of galaxies, as (galaxy index, galaxy index, distance)
+    fn pair_distances(&self, expansion: &Expansion) -> Vec<(usize, usize, u64)> {
+        self.expanded_galaxies(expansion)
+            .iter()
+            .enumerate()
+            .tuple_combinations()
+            .map(|((i, g1), (j, g2))| (i, j, g1[0].abs_diff(g2[0]) + g1[1].abs_diff(g2[1])))
+            .collect()
+    }
+}
+
 fn main() {
-    let input = aoc2023_rust::read_input_lines("input/day11.txt");
-    let width = input[0].len();
-    let height = input.len();
-    let input = input.join("").into_bytes();
+    let board = aoc2023_rust::read_2d_board("input/day11.txt");
+    let universe = Universe::new(board.view());
+
+    println!("Part 1: {}", universe.distances_sum(&Expansion::uniform(2)));
+
+    let expansion = Expansion::uniform(1_000_000);
+    println!("Part 2: {}", universe.distances_sum(&expansion));
+
+    if std::env::args().any(|arg| arg == "--pairs") {
+        let pairs = universe.pair_distances(&expansion);
+        let closest = pairs.iter().min_by_key(|(_, _, distance)| distance);
+        let farthest = pairs.iter().max_by_key(|(_, _, distance)| distance);
+        for (name, pair) in [("Closest", closest), ("Farthest", farthest)] {
+            if let Some(&(i, j, distance)) = pair {
+                println!("{} pair: {:?} and {:?}, distance {}",
+                         name, universe.galaxies[i], universe.galaxies[j], distance);
+            }
+        }
+    }
+}
 
-    let board = Array2::from_shape_vec((height, width), input).unwrap();
+#[cfg(test)]
+fn example_universe() -> Universe {
+    let lines = [
+        "...#......",
+        ".......#..",
+        "#.........",
+        "..........",
+        "......#...",
+        ".#........",
+        ".........#",
+        "..........",
+        ".......#..",
+        "#...#.....",
+    ];
+    let board = aoc2023_rust::InputBuffer::new(lines.join("\n")).to_2d_board();
+    Universe::new(board.view())
+}
+
+#[test]
+fn day11_example() {
+    use aoc2023_rust::geometry::pairwise_manhattan_sum;
 
-    let sum_line =
-        |line: ArrayView1<u8>| line.iter().filter(|&&c| c == b'#').count() as u64;
+    let universe = example_universe();
+    assert!(universe.distances_sum(&Expansion::uniform(2)) == 374);
+    assert!(universe.distances_sum(&Expansion::uniform(10)) == 1030);
+    assert!(universe.distances_sum(&Expansion::uniform(100)) == 8410);
 
-    let rows_counts: Vec<_> = board.rows()   .into_iter().map(sum_line).collect();
-    let cols_counts: Vec<_> = board.columns().into_iter().map(sum_line).collect();
+    let expanded = universe
+        .expanded_galaxies(&Expansion::uniform(100))
+        .iter()
+        .map(|&[y, x]| [y as i64, x as i64])
+        .collect_vec();
+    assert!(pairwise_manhattan_sum(&expanded) == 8410);
+}
+
+#[test]
+fn day11_pairs() {
+    let universe = example_universe();
+    let expansion = Expansion::uniform(2);
+    let pairs = universe.pair_distances(&expansion);
+
+    assert!(pairs.len() == 36);
+    assert!(pairs.iter().map(|(_, _, distance)| distance).sum::<u64>() == 374);
+    // galaxies 5 and 9 from the puzzle description
+    assert!(pairs.contains(&(4, 8, 9)));
+    assert!(universe.expanded_galaxies(&expansion)[0] == [0, 4]);
+}
+
+#[test]
+fn day11_expansion_per_line() {
+    let universe = example_universe();
+    // empty rows are 3 and 7, empty columns are 2, 5 and 8
+    let expansion = Expansion {
+        rows: 1,
+        columns: 3,
+        custom_rows: HashMap::from([(7, 5)]),
+        custom_columns: HashMap::from([(5, 1)])
+    };
+    let pairs = universe.pair_distances(&expansion);
+    let expected_sum: u64 = pairs.iter().map(|(_, _, distance)| distance).sum();
+    assert!(universe.distances_sum(&expansion) == expected_sum);
+    assert!(universe.expanded_galaxies(&expansion)[8] == [9 + 4, 4 + 2]);
+}
+
+/// Measures every pair of galaxies, counting the empty lines between them one by one
+#[cfg(test)]
+mod reference {
+    use itertools::Itertools;
+
+    pub fn distances_sum(lines: &[&str], expansion_rate: u64) -> u64 {
+        let galaxies = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| line.match_indices('#').map(move |(x, _)| [y, x]))
+            .collect_vec();
+        let is_empty_row = |y: usize| !lines[y].contains('#');
+        let is_empty_column = |x: usize| lines.iter().all(|line| line.as_bytes()[x] != b'#');
+
+        let distance = |from: usize, to: usize, is_empty: &dyn Fn(usize) -> bool| -> u64 {
+            (from.min(to)..from.max(to)).map(|line| if is_empty(line) { expansion_rate } else { 1 }).sum()
+        };
+
+        galaxies
+            .iter()
+            .tuple_combinations()
+            .map(|(g1, g2)| distance(g1[0], g2[0], &is_empty_row) + distance(g1[1], g2[1], &is_empty_column))
+            .sum()
+    }
+}
 
-    let row_distances = distances_after_expansion(&rows_counts, 2);
-    let col_distances = distances_after_expansion(&cols_counts, 2);
+#[test]
+fn day11_against_reference() {
+    use aoc2023_rust::differential::assert_agree;
+    use aoc2023_rust::generate::Day11;
 
-    println!("Part 1: {}", row_distances + col_distances);
 
-    let row_distances2 = distances_after_expansion(&rows_counts, 1_000_000);
-    let col_distances2 = distances_after_expansion(&cols_counts, 1_000_000);
+    let solve = |text: &str| {
+        let board = aoc2023_rust::InputBuffer::new(text.to_string()).to_2d_board();
+        let universe = Universe::new(board.view());
+        [2, 10].map(|rate| universe.distances_sum(&Expansion::uniform(rate)))
+    };
+    let reference = |text: &str| [2, 10].map(|rate| reference::distances_sum(&text.lines().collect::<Vec<_>>(), rate));
 
-    println!("Part 2: {}", row_distances2 + col_distances2);
+    let generator = Day11 { width: 20, height: 15, density: 0.1 };
+    assert_agree(generator, 200, reference, solve);
 }
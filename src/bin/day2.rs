@@ -1,78 +1,287 @@
+use std::collections::HashMap;
+use std::num::ParseIntError;
+
 use aoc2023_rust::*;
 use itertools::Itertools;
+use thiserror::Error;
 
-fn process_line(line: &str) -> (u32, u32, u32) {
-    // "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
-    let line = line.split_once(": ").unwrap().1;
-    // "3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
+type Colour = String;
 
-    fn parse_num_first(s: &str) -> u32 {
-        // "12 text"
-        s.split_once(' ').unwrap().0.parse().unwrap()
-    }
+/// Number of cubes of each colour, in a single draw or in a bag
+type Cubes = HashMap<Colour, u32>;
+
+struct Game {
+    id: u32,
+    draws: Vec<Cubes>
+}
+
+#[derive(Error, Debug)]
+enum ParseGameError {
+    #[error("Game should start with \"Game <id>: \"")]
+    MissingId,
+
+    #[error("Cubes should be described as \"<count> <colour>\"")]
+    WrongCubes,
+
+    #[error(transparent)]
+    Int(#[from] ParseIntError)
+}
 
-    fn parse_draw(s: &str) -> (u32, u32, u32) {
-        // "1 red, 2 green, 6 blue"
-        let mut balls = (0, 0, 0);
-        for color in s.split(", ") {
-            if color.ends_with("red") {
-                balls.0 = parse_num_first(color);
-            } else if color.ends_with("green") {
-                balls.1 = parse_num_first(color);
-            } else if color.ends_with("blue") {
-                balls.2 = parse_num_first(color);
-            } else {
-                panic!("Could not find any color in the fragment")
+impl std::str::FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
+        let (game, draws) = s.split_once(": ").ok_or(ParseGameError::MissingId)?;
+        let id = game.strip_prefix("Game ").ok_or(ParseGameError::MissingId)?.parse()?;
+
+        fn parse_draw(s: &str) -> Result<Cubes, ParseGameError> {
+            // "1 red, 2 green, 6 blue"
+            let mut cubes = Cubes::new();
+            for fragment in s.split(", ") {
+                let (count, colour) = fragment.split_once(' ').ok_or(ParseGameError::WrongCubes)?;
+                *cubes.entry(colour.to_string()).or_default() += count.parse::<u32>()?;
             }
+            Ok(cubes)
         }
 
-        balls
+        let draws = draws.split("; ").map(parse_draw).try_collect()?;
+        Ok(Game { id, draws })
     }
+}
+
+/// Statistics of a single draw
+#[derive(PartialEq, Debug)]
+struct DrawStatistics {
+    cubes: u32,
+    colours: usize
+}
+
+/// A draw that could not come from a bag
+#[derive(PartialEq, Debug)]
+struct Violation {
+    // index of the draw in the game
+    draw: usize,
+    colour: Colour,
+    drawn: u32,
+    in_bag: u32
+}
 
-    fn tuple_max<T: Ord> (t1: (T, T, T), t2: (T, T, T)) -> (T, T, T) {
-        (t1.0.max(t2.0), t1.1.max(t2.1), t1.2.max(t2.2))
+impl Game {
+    /// Could all the draws come from the bag
+    fn is_possible_with(&self, bag: &Cubes) -> bool {
+        self.violation(bag).is_none()
     }
 
-    line.split("; ")
-        .map(parse_draw)
-        .fold((0, 0, 0), tuple_max)
+    /// The first draw, and its colour, that rules out the bag
+    fn violation(&self, bag: &Cubes) -> Option<Violation> {
+        self.draws.iter().enumerate().find_map(|(idx, draw)| {
+            draw.iter()
+                .sorted()
+                .map(|(colour, &drawn)| (colour, drawn, *bag.get(colour).unwrap_or(&0)))
+                .find(|&(_, drawn, in_bag)| drawn > in_bag)
+                .map(|(colour, drawn, in_bag)| Violation { draw: idx, colour: colour.clone(), drawn, in_bag })
+        })
+    }
+
+    /// The smallest bag all the draws could come from
+    fn minimal_bag(&self) -> Cubes {
+        let mut bag = Cubes::new();
+        for (colour, &count) in self.draws.iter().flatten() {
+            let max = bag.entry(colour.clone()).or_default();
+            *max = count.max(*max);
+        }
+        bag
+    }
+
+    fn draw_statistics(&self) -> Vec<DrawStatistics> {
+        self.draws
+            .iter()
+            .map(|draw| DrawStatistics {
+                cubes: draw.values().sum(),
+                colours: draw.values().filter(|&&count| count > 0).count()
+            })
+            .collect()
+    }
 }
 
+/// The smallest bag all the games could come from
+fn minimal_bag_for_all(games: &[Game]) -> Cubes {
+    let mut bag = Cubes::new();
+    for (colour, count) in games.iter().flat_map(Game::minimal_bag) {
+        let max = bag.entry(colour).or_default();
+        *max = count.max(*max);
+    }
+    bag
+}
 
-fn main() {
-    let lines = read_input_lines("input/day2.txt");
-    let games_maxes = lines
+#[derive(Error, Debug, PartialEq)]
+#[error("Colour {0} is drawn in the games, but has no bound")]
+struct UnboundedColour(Colour);
+
+/// All the bags, with at most `bounds` cubes of each colour, that all the games could come from.
+/// Fails if a colour drawn in the games is missing in `bounds`.
+fn consistent_bags<'a>(games: &[Game], bounds: &'a Cubes) -> Result<impl Iterator<Item = Cubes> + 'a, UnboundedColour> {
+    let minimal_bag = minimal_bag_for_all(games);
+    if let Some(colour) = minimal_bag.keys().filter(|colour| !bounds.contains_key(*colour)).min() {
+        return Err(UnboundedColour(colour.clone()));
+    }
+
+    let bags = bounds
         .iter()
-        .map(|s| process_line(s))
-        .collect_vec();
+        .sorted()
+        .map(|(colour, &bound)| {
+            let min = *minimal_bag.get(colour).unwrap_or(&0);
+            (min..=bound).map(move |count| (colour.clone(), count))
+        })
+        .multi_cartesian_product()
+        .map(|bag| bag.into_iter().collect());
+    Ok(bags)
+}
 
-    const BALLS_MAX: (u32, u32, u32) = (12, 13, 14);
-    let part1: u32 = games_maxes
+/// "12 red, 13 green, 14 blue", colours in alphabetical order
+fn format_cubes(cubes: &Cubes) -> String {
+    cubes.iter().sorted().map(|(colour, count)| format!("{} {}", count, colour)).join(", ")
+}
+
+/// Maximal number of red, green and blue cubes drawn in the game
+fn rgb_maxes(game: &Game) -> (u32, u32, u32) {
+    let bag = game.minimal_bag();
+    let count = |colour| *bag.get(colour).unwrap_or(&0);
+    (count("red"), count("green"), count("blue"))
+}
+
+fn main() {
+    let input = read_input("input/day2.txt");
+    let games: Vec<Game> = input
+        .lines()
+        .map(|line| line.parse().unwrap())
+        .collect();
+
+    let bag = Cubes::from([
+        ("red".to_string(), 12),
+        ("green".to_string(), 13),
+        ("blue".to_string(), 14)
+    ]);
+    let part1: u32 = games
         .iter()
-        .enumerate()
-        .filter_map(
-            |(idx, balls)|
-                if balls.0 <= BALLS_MAX.0
-                && balls.1 <= BALLS_MAX.1
-                && balls.2 <= BALLS_MAX.2 {
-                    Some((idx + 1) as u32)
-                } else {
-                    None
-                })
+        .filter(|game| game.is_possible_with(&bag))
+        .map(|game| game.id)
         .sum();
 
     println!("Part 1: {}", part1);
 
-    let part2: u32 = games_maxes.iter().map(|balls| balls.0 * balls.1 * balls.2).sum();
+    let part2: u32 = games.iter().map(rgb_maxes).map(|balls| balls.0 * balls.1 * balls.2).sum();
     println!("Part 2: {}", part2);
+
+    if std::env::args().any(|arg| arg == "--explain") {
+        for game in &games {
+            if let Some(Violation { draw, colour, drawn, in_bag }) = game.violation(&bag) {
+                println!("Game {}: draw {} has {} {}, the bag has {}", game.id, draw + 1, drawn, colour, in_bag);
+            }
+        }
+    }
+
+    // `--infer` lists the first bags within the bounds of part 1 and counts all of them
+    if std::env::args().any(|arg| arg == "--infer") {
+        const SHOWN_BAGS: usize = 20;
+
+        let minimal_bag = minimal_bag_for_all(&games);
+        println!("Minimal bag: {}, {} cubes", format_cubes(&minimal_bag), minimal_bag.values().sum::<u32>());
+
+        match consistent_bags(&games, &bag) {
+            Ok(bags) => {
+                println!("Bags up to {}:", format_cubes(&bag));
+                let mut count = 0;
+                for consistent_bag in bags {
+                    if count < SHOWN_BAGS {
+                        println!("  {}", format_cubes(&consistent_bag));
+                    }
+                    count += 1;
+                }
+                println!("{} bags in total", count);
+
+                let exceeded = minimal_bag.iter().filter(|(colour, &count)| count > bag[*colour]).sorted().collect_vec();
+                for (colour, count) in exceeded {
+                    println!("No bag fits: the games need {} {}, the bound is {}", count, colour, bag[colour]);
+                }
+            }
+            Err(err) => println!("{}", err)
+        }
+    }
+
+    if std::env::args().any(|arg| arg == "--stats") {
+        for game in &games {
+            let draws = game.draw_statistics()
+                .iter()
+                .map(|stats| format!("{} cubes in {} colours", stats.cubes, stats.colours))
+                .join("; ");
+            println!("Game {}: {}", game.id, draws);
+        }
+    }
 }
 
 
 #[test]
 fn day2_example() {
-    assert!(process_line("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green") == (4, 2, 6));
-    assert!(process_line("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue") == (1, 3, 4));
-    assert!(process_line("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red") == (20, 13, 6));
-    assert!(process_line("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red") == (14, 3, 15));
-    assert!(process_line("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green") == (6, 3, 2));
+    assert!(rgb_maxes(&"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".parse().unwrap()) == (4, 2, 6));
+    assert!(rgb_maxes(&"Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".parse().unwrap()) == (1, 3, 4));
+    assert!(rgb_maxes(&"Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red".parse().unwrap()) == (20, 13, 6));
+    assert!(rgb_maxes(&"Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red".parse().unwrap()) == (14, 3, 15));
+    assert!(rgb_maxes(&"Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".parse().unwrap()) == (6, 3, 2));
+}
+
+#[test]
+fn day2_arbitrary_colours() {
+    let game: Game = "Game 17: 3 cyan, 4 magenta; 1 yellow, 2 cyan".parse().unwrap();
+    assert!(game.id == 17);
+    assert!(game.minimal_bag() == Cubes::from([
+        ("cyan".to_string(), 3),
+        ("magenta".to_string(), 4),
+        ("yellow".to_string(), 1)
+    ]));
+
+    let bag = game.minimal_bag();
+    assert!(game.is_possible_with(&bag));
+    let smaller_bag = Cubes::from([("cyan".to_string(), 3), ("magenta".to_string(), 4)]);
+    assert!(!game.is_possible_with(&smaller_bag));
+
+    assert!(game.draw_statistics() == [
+        DrawStatistics { cubes: 7, colours: 2 },
+        DrawStatistics { cubes: 3, colours: 2 }
+    ]);
+
+    assert!(matches!("3 cyan".parse::<Game>(), Err(ParseGameError::MissingId)));
+    assert!(matches!("Game 1: cyan".parse::<Game>(), Err(ParseGameError::WrongCubes)));
+}
+
+#[test]
+fn day2_bag_inference() {
+    let games: Vec<Game> = [
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+    ].iter().map(|line| line.parse().unwrap()).collect();
+    let cubes = |counts: [(&str, u32); 3]| counts.map(|(colour, count)| (colour.to_string(), count)).into();
+
+    assert!(minimal_bag_for_all(&games) == cubes([("red", 20), ("green", 13), ("blue", 6)]));
+
+    let bounds = cubes([("red", 21), ("green", 13), ("blue", 7)]);
+    let bags = consistent_bags(&games, &bounds).unwrap().collect_vec();
+    assert!(bags.len() == 4);
+    assert!(bags.iter().all(|bag| games.iter().all(|game| game.is_possible_with(bag))));
+    assert!(bags.contains(&cubes([("red", 21), ("green", 13), ("blue", 6)])));
+
+    let without_blue = Cubes::from([("red".to_string(), 30), ("green".to_string(), 30)]);
+    assert!(consistent_bags(&games, &without_blue).err() == Some(UnboundedColour("blue".to_string())));
+    // returns at once, without going through the 100^5 combinations
+    let wide_without_blue = ["red", "green", "cyan", "magenta", "yellow"].map(|colour| (colour.to_string(), 100)).into();
+    assert!(consistent_bags(&games, &wide_without_blue).is_err());
+    // the games need 20 red
+    let too_few_red = cubes([("red", 19), ("green", 20), ("blue", 20)]);
+    assert!(consistent_bags(&games, &too_few_red).unwrap().next().is_none());
+
+    let bag = cubes([("red", 12), ("green", 13), ("blue", 14)]);
+    assert!(games[0].violation(&bag).is_none());
+    assert!(games[2].violation(&bag) == Some(Violation { draw: 0, colour: "red".to_string(), drawn: 20, in_bag: 12 }));
 }
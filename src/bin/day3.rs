@@ -1,99 +1,289 @@
 use aoc2023_rust::*;
+use aoc2023_rust::grid::{digit_runs, Adjacency, DigitRun, DigitRunOverflow, GridIndex};
 use itertools::Itertools;
-
-// Describes a position of a number in the board.
-// The ranges include the border around the number.
-// Example:
-// It could describe the position of the following region (and number's value):
-// .....
-// .123.
-// .....
-struct NumberInGrid {
-    x_range: std::ops::RangeInclusive<isize>,
-    y_range: std::ops::RangeInclusive<isize>,
-    value: u32
-}
+use ndarray::ArrayView2;
 
 struct SymbolInGrid {
-    x: isize,
-    y: isize,
+    x: usize,
+    y: usize,
     symbol: char
 }
 
-impl NumberInGrid {
-    fn new(value: u32, x_end: isize, y: isize) -> Self {
-        let len = value.to_string().len() as isize;
-        let x_range = (x_end - len - 1)..=(x_end);
-        let y_range = (y - 1)..=(y + 1);
-        NumberInGrid { x_range, y_range, value }
+/// Which characters are symbols, digits never are
+enum SymbolRule {
+    AllExcept(Vec<char>),
+    Only(Vec<char>)
+}
+
+impl SymbolRule {
+    fn is_symbol(&self, c: char) -> bool {
+        !c.is_ascii_digit() && match self {
+            SymbolRule::AllExcept(chars) => !chars.contains(&c),
+            SymbolRule::Only(chars) => chars.contains(&c)
+        }
     }
+}
+
+#[derive(Clone, Copy)]
+enum Aggregation {
+    Product,
+    Sum
+}
 
-    fn touches_symbol(&self, symbol: &SymbolInGrid) -> bool {
-        self.x_range.contains(&symbol.x) &&
-        self.y_range.contains(&symbol.y)
+/// A gear is a symbol touching between `min_numbers` and `max_numbers` numbers, inclusive.
+/// Its ratio aggregates the values of these numbers.
+struct GearRule {
+    symbol: char,
+    min_numbers: usize,
+    max_numbers: usize,
+    aggregation: Aggregation
+}
+
+fn parse_gear_rule(s: &str) -> Option<GearRule> {
+    let (symbol, min_numbers, max_numbers, aggregation) = s.split(',').collect_tuple()?;
+    Some(GearRule {
+        symbol: symbol.chars().exactly_one().ok()?,
+        min_numbers: min_numbers.parse().ok()?,
+        max_numbers: max_numbers.parse().ok()?,
+        aggregation: match aggregation {
+            "product" => Aggregation::Product,
+            "sum" => Aggregation::Sum,
+            _ => return None
+        }
+    })
+}
+
+struct Rules {
+    symbols: SymbolRule,
+    adjacency: Adjacency,
+    gear: GearRule
+}
+
+impl Default for Rules {
+    /// Rules from the puzzle: all but '.' are symbols, a gear is '*' touching exactly two numbers
+    fn default() -> Self {
+        Rules {
+            symbols: SymbolRule::AllExcept(vec!['.']),
+            adjacency: Adjacency::Eight,
+            gear: GearRule { symbol: '*', min_numbers: 2, max_numbers: 2, aggregation: Aggregation::Product }
+        }
     }
 }
 
-fn parse_grid(lines: &[String]) -> (Vec<NumberInGrid>, Vec<SymbolInGrid>) {
-    let mut numbers: Vec<NumberInGrid> = Vec::new();
-    let mut symbols: Vec<SymbolInGrid> = Vec::new();
+struct Schematic {
+    rules: Rules,
+    numbers: Vec<DigitRun>,
+    symbols: Vec<SymbolInGrid>,
+    // cells covered by numbers, IDs are indices in `numbers`
+    number_index: GridIndex
+}
 
-    for (y, line) in lines.iter().enumerate() {
-        let mut next_number = 0;
-        for (x, c) in line.chars().enumerate() {
-            if let Some(digit) = c.to_digit(10) {
-                next_number *= 10;
-                next_number += digit;
-            }
-            else {
-                if next_number > 0 {
-                    numbers.push(NumberInGrid::new(next_number, x as isize, y as isize));
-                    next_number = 0;
-                }
-
-                if c != '.' {
-                    symbols.push(SymbolInGrid{ x: x as isize, y: y as isize, symbol: c })
-                }
+impl Schematic {
+    /// Indices of the numbers touching the symbol
+    fn numbers_around(&self, symbol: &SymbolInGrid) -> Vec<usize> {
+        self.number_index.around([symbol.y, symbol.x], self.rules.adjacency)
+    }
+
+    /// Values of the numbers touching each symbol
+    fn symbol_report(&self) -> Vec<(&SymbolInGrid, Vec<u64>)> {
+        self.symbols
+            .iter()
+            .map(|s| (s, self.numbers_around(s).iter().map(|&id| self.numbers[id].value).collect()))
+            .collect()
+    }
+}
+
+fn parse_grid(board: ArrayView2<u8>, rules: Rules) -> Result<Schematic, DigitRunOverflow> {
+    let numbers = digit_runs(board)?;
+
+    let symbols = board
+        .indexed_iter()
+        .filter(|(_, &c)| rules.symbols.is_symbol(c as char))
+        .map(|((y, x), &c)| SymbolInGrid { x, y, symbol: c as char })
+        .collect();
+
+    let mut number_index = GridIndex::new(board.dim());
+    for (id, number) in numbers.iter().enumerate() {
+        number_index.insert(id, number.columns.clone().map(|x| [number.row, x]));
+    }
+
+    Ok(Schematic { rules, numbers, symbols, number_index })
+}
+
+fn part1(schematic: &Schematic) -> u64 {
+    let mut is_part_number = vec![false; schematic.numbers.len()];
+    for symbol in &schematic.symbols {
+        for id in schematic.numbers_around(symbol) {
+            is_part_number[id] = true;
+        }
+    }
+
+    schematic.numbers
+        .iter()
+        .zip(is_part_number)
+        .filter(|(_, is_part_number)| *is_part_number)
+        .map(|(number, _)| number.value)
+        .sum()
+}
+
+fn part2(schematic: &Schematic) -> u64 {
+    let GearRule { symbol, min_numbers, max_numbers, aggregation } = schematic.rules.gear;
+
+    schematic.symbols.iter()
+        .filter(|s| s.symbol == symbol)
+        .map(|s| schematic.numbers_around(s))
+        .filter(|touching| (min_numbers..=max_numbers).contains(&touching.len()))
+        .map(|touching| {
+            let values = touching.iter().map(|&id| schematic.numbers[id].value);
+            match aggregation {
+                Aggregation::Product => values.product::<u64>(),
+                Aggregation::Sum => values.sum()
             }
+        })
+        .sum()
+}
+
+fn main() {
+    // `--symbols CHARS` limits symbols to the given characters,
+    // `--adjacency 4` counts only numbers sharing a side with a symbol,
+    // `--gear SYMBOL,MIN,MAX,product|sum` changes the gear rule, e.g. `--gear *,2,2,product`
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut rules = Rules::default();
+    if let Some(symbols) = flag_value(&args, "--symbols") {
+        rules.symbols = SymbolRule::Only(symbols.chars().collect());
+    }
+    if flag_value(&args, "--adjacency").is_some_and(|adjacency| adjacency == "4") {
+        rules.adjacency = Adjacency::Four;
+    }
+    if let Some(gear) = flag_value(&args, "--gear") {
+        rules.gear = parse_gear_rule(gear).expect("Gear rule should look like *,2,2,product");
+    }
+
+    let board = read_2d_board("input/day3.txt");
+    let schematic = match parse_grid(board.view(), rules) {
+        Ok(schematic) => schematic,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
         }
+    };
 
-        if next_number > 0 {
-            numbers.push(NumberInGrid::new(next_number,line.len() as isize, y as isize));
+    println!("Part1: {}", part1(&schematic));
+    println!("Part2: {}", part2(&schematic));
+
+    if args.iter().any(|arg| arg == "--report") {
+        for (symbol, values) in schematic.symbol_report() {
+            println!("'{}' at [{}, {}]: {:?}", symbol.symbol, symbol.y, symbol.x, values);
         }
     }
+}
 
-    (numbers, symbols)
+#[cfg(test)]
+fn parse_lines(lines: &[&str], rules: Rules) -> Schematic {
+    let board = InputBuffer::new(lines.join("\n")).to_2d_board();
+    parse_grid(board.view(), rules).unwrap()
 }
 
-fn main() {
-    let lines = read_input_lines("input/day3.txt");
-    let (numbers, symbols) = parse_grid(&lines);
+#[test]
+fn day3_example() {
+    let schematic = parse_lines(&[
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ], Rules::default());
+    assert!(part1(&schematic) == 4361);
+    assert!(part2(&schematic) == 467835);
+}
 
-    let part1: u32 = numbers
+#[test]
+fn day3_zeros_and_edges() {
+    let schematic = parse_lines(&[
+        "0.....007",
+        "#......*.",
+        "10*2...12",
+        "5........",
+    ], Rules::default());
+    // 0, 007, 10, 2 and 12 touch symbols, 5 on the next line does not continue 12
+    assert!(schematic.numbers.len() == 6);
+    assert!(part1(&schematic) == 7 + 10 + 2 + 12);
+    assert!(part2(&schematic) == 10 * 2 + 7 * 12);
+}
+
+#[test]
+fn day3_custom_rules() {
+    let lines = [
+        "1.2..3",
+        ".*#.$.",
+        "4..5..",
+    ];
+
+    let schematic = parse_lines(&lines, Rules::default());
+    assert!(part1(&schematic) == 1 + 2 + 3 + 4 + 5);
+    assert!(part2(&schematic) == 0);
+    let report = schematic.symbol_report();
+    assert!(report.iter().map(|(s, values)| (s.symbol, values.clone())).collect::<Vec<_>>()
+        == [('*', vec![1, 2, 4]), ('#', vec![2, 5]), ('$', vec![3, 5])]);
+
+    let schematic = parse_lines(&lines, Rules {
+        symbols: SymbolRule::Only(vec!['*', '#']),
+        adjacency: Adjacency::Four,
+        gear: GearRule { symbol: '#', min_numbers: 1, max_numbers: 3, aggregation: Aggregation::Sum }
+    });
+    // only 2 shares a side with a symbol, '$' is not a symbol
+    assert!(part1(&schematic) == 2);
+    assert!(part2(&schematic) == 2);
+
+    let schematic = parse_lines(&lines, Rules {
+        gear: GearRule { symbol: '#', min_numbers: 1, max_numbers: 2, aggregation: Aggregation::Sum },
+        ..Rules::default()
+    });
+    assert!(part2(&schematic) == 2 + 5);
+
+    let schematic = parse_lines(&lines, Rules {
+        gear: GearRule { symbol: '*', min_numbers: 3, max_numbers: 3, aggregation: Aggregation::Product },
+        ..Rules::default()
+    });
+    assert!(part2(&schematic) == 8);
+}
+
+/// Generates a 10k x 10k schematic and checks part 1 against looking at each number's border.
+/// Run with `cargo test --release --bin day3 -- --ignored`
+#[test]
+#[ignore]
+fn day3_large_schematic() {
+    use ndarray::Array2;
+    use rand::{Rng, SeedableRng};
+
+    const SIZE: usize = 10_000;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    let board = Array2::from_shape_simple_fn((SIZE, SIZE), || match rng.gen_range(0..20) {
+        // runs of digits stay short enough to fit in u64
+        0..=11 => b'.',
+        12 => b'*',
+        13 => b'#',
+        _ => b'0' + rng.gen_range(0..10),
+    });
+
+    let schematic = parse_grid(board.view(), Rules::default()).unwrap();
+
+    let is_symbol = |c: &u8| *c != b'.' && !c.is_ascii_digit();
+    let expected: u64 = schematic.numbers
         .iter()
         .filter(|number| {
-            symbols.iter().any(|s| number.touches_symbol(s))
+            let rows = number.row.saturating_sub(1)..(number.row + 2).min(SIZE);
+            let columns = number.columns.start.saturating_sub(1)..(number.columns.end + 1).min(SIZE);
+            board.slice(ndarray::s![rows, columns]).iter().any(is_symbol)
         })
         .map(|number| number.value)
         .sum();
 
-    println!("Part1: {}", part1);
-
-    let part2: u32 = symbols.iter()
-        .filter(|s| s.symbol == '*')
-        .filter_map(|s| {
-            let touching = numbers
-                .iter()
-                .filter(|n| n.touches_symbol(s))
-                .collect_vec();
-            if let &[first, second] = &touching[..] {
-                Some(first.value * second.value)
-            } else {
-                None
-            }
-        })
-        .sum();
-
-    println!("Part2: {}", part2);
+    assert!(part1(&schematic) == expected);
 }
-
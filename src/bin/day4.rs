@@ -1,43 +1,276 @@
+use std::num::ParseIntError;
+
 use aoc2023_rust::*;
+use aoc2023_rust::number_set::NumberSet;
+use itertools::{Either, Itertools};
+use thiserror::Error;
 
-fn line_to_win_count(line: &str) -> usize {
-    let line = line.split_once(':').unwrap().1;
-    let (left, right) = line.split_once('|').unwrap();
+struct Card {
+    id: usize,
+    // numbers as written, duplicates included
+    winning: Vec<usize>,
+    held: Vec<usize>
+}
 
-    let parse_list_of_numbers = |s: &str| -> Vec<usize> {
-        s
-        .split(' ')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse().unwrap())
-        .collect()
-    };
+#[derive(Error, Debug, PartialEq)]
+enum ParseCardError {
+    #[error("Card should start with \"Card <id>:\"")]
+    MissingId,
+
+    #[error("Winning and held numbers should be separated with '|'")]
+    MissingSeparator,
+
+    #[error(transparent)]
+    Int(#[from] ParseIntError)
+}
+
+impl std::str::FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+        let (label, numbers) = s.split_once(':').ok_or(ParseCardError::MissingId)?;
+        let id = label.strip_prefix("Card").ok_or(ParseCardError::MissingId)?.trim().parse()?;
+        let (winning, held) = numbers.split_once('|').ok_or(ParseCardError::MissingSeparator)?;
+
+        let parse_list_of_numbers = |s: &str| -> Result<Vec<usize>, ParseIntError> {
+            s
+            .split(' ')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .collect()
+        };
+
+        Ok(Card { id, winning: parse_list_of_numbers(winning)?, held: parse_list_of_numbers(held)? })
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Line {line_number}: {error}")]
+struct BadLine {
+    // counted from 1
+    line_number: usize,
+    error: ParseCardError
+}
+
+/// Cards of the lines that parse, and the errors of the ones that do not
+fn parse_cards<'a>(lines: impl Iterator<Item = &'a str>) -> (Vec<Card>, Vec<BadLine>) {
+    lines
+        .enumerate()
+        .partition_map(|(idx, line)| match line.parse() {
+            Ok(card) => Either::Left(card),
+            Err(error) => Either::Right(BadLine { line_number: idx + 1, error })
+        })
+}
+
+impl Card {
+    /// Number of distinct winning numbers among the held ones
+    fn win_count(&self) -> usize {
+        NumberSet::common_count(&self.winning, &self.held)
+    }
+}
+
+/// A problem with valid looking cards
+#[derive(Error, Debug, PartialEq)]
+enum CardIssue {
+    #[error("Card {found} should have id {expected}")]
+    NonSequentialId { expected: usize, found: usize },
+
+    #[error("Card {card} has winning number {number} more than once")]
+    DuplicateWinning { card: usize, number: usize },
+
+    #[error("Card {card} has held number {number} more than once")]
+    DuplicateHeld { card: usize, number: usize }
+}
+
+/// Checks that the ids go 1, 2, 3... and that no card repeats a number on either side
+fn validate(cards: &[Card]) -> Vec<CardIssue> {
+    let mut issues = Vec::new();
+    for (idx, card) in cards.iter().enumerate() {
+        if card.id != idx + 1 {
+            issues.push(CardIssue::NonSequentialId { expected: idx + 1, found: card.id });
+        }
+
+        let duplicates = |numbers: &[usize]| numbers.iter().duplicates().cloned().sorted().collect_vec();
+        for number in duplicates(&card.winning) {
+            issues.push(CardIssue::DuplicateWinning { card: card.id, number });
+        }
+        for number in duplicates(&card.held) {
+            issues.push(CardIssue::DuplicateHeld { card: card.id, number });
+        }
+    }
+    issues
+}
+
+/// What to do when a card wins copies of cards past the last one
+#[derive(Clone, Copy)]
+enum Overflow {
+    /// Win copies of the remaining cards only
+    Clamp,
+    Error
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Card {card} wins copies of {win_count} cards, but only {remaining} follow it")]
+struct OverflowError {
+    card: usize,
+    win_count: usize,
+    remaining: usize
+}
+
+/// Copies of each card after all the cards are scratched
+struct Cascade {
+    // in the order of the cards, originals included
+    copies: Vec<usize>,
+    // for each card, ids of the earlier cards it was won from and how many copies each gave
+    received_from: Vec<Vec<(usize, usize)>>
+}
 
-    let nums_left = parse_list_of_numbers(left);
-    let nums_right = parse_list_of_numbers(right);
+/// Each card wins one copy of each of the next `win_count` cards, for every copy of itself
+fn cascade(cards: &[(usize, usize)], overflow: Overflow) -> Result<Cascade, OverflowError> {
+    let mut copies = vec![1usize; cards.len()];
+    let mut received_from = vec![Vec::new(); cards.len()];
+
+    for (i, &(id, win_count)) in cards.iter().enumerate() {
+        let remaining = cards.len() - i - 1;
+        if win_count > remaining {
+            if let Overflow::Error = overflow {
+                return Err(OverflowError { card: id, win_count, remaining });
+            }
+        }
 
-    nums_left.iter().filter(|x| nums_right.contains(x)).count()
+        for j in (i + 1)..(i + 1 + win_count.min(remaining)) {
+            copies[j] += copies[i];
+            received_from[j].push((id, copies[i]));
+        }
+    }
+
+    Ok(Cascade { copies, received_from })
 }
 
 fn main() {
-    let win_counts: Vec<_> =
-        read_input_lines("input/day4.txt")
-        .iter()
-        .map(|s| line_to_win_count(s))
-        .collect();
+    // `--overflow error` fails on cards winning copies past the last card, instead of ignoring these copies,
+    // `--explain` lists the copies of each card and where they come from
+    let args: Vec<String> = std::env::args().collect();
+    let overflow = match flag_value(&args, "--overflow") {
+        None | Some("clamp") => Overflow::Clamp,
+        Some("error") => Overflow::Error,
+        Some(other) => panic!("Unknown overflow \"{}\", expected clamp or error", other)
+    };
+
+    // the lines that do not parse are reported and skipped
+    let (cards, bad_lines) = parse_cards(read_input("input/day4.txt").lines());
+    for bad_line in &bad_lines {
+        eprintln!("{}", bad_line);
+    }
+    for issue in validate(&cards) {
+        eprintln!("{}", issue);
+    }
 
-    let part1: usize = win_counts
+    let cards = cards.iter().map(|card| (card.id, card.win_count())).collect_vec();
+
+    let part1: usize = cards
         .iter()
-        .map(|&win_count| if win_count > 0 { 1 << (win_count - 1) } else { 0 }).sum();
+        .map(|&(_, win_count)| if win_count > 0 { 1 << (win_count - 1) } else { 0 }).sum();
     println!("Part 1: {}", part1);
 
-    let mut cards_count = vec![1usize; win_counts.len()];
+    let cascade = match cascade(&cards, overflow) {
+        Ok(cascade) => cascade,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
 
-    for (i, win_count) in win_counts.iter().enumerate() {
-        for j in (i + 1)..(i + 1 + win_count) {
-            cards_count[j] += cards_count[i]
+    let part2: usize = cascade.copies.iter().sum();
+    println!("Part 2: {}", part2);
+
+    if args.iter().any(|arg| arg == "--explain") {
+        for ((id, _), (copies, received_from)) in cards.iter().zip(cascade.copies.iter().zip(&cascade.received_from)) {
+            let sources = received_from
+                .iter()
+                .map(|(source, count)| format!("{} from card {}", count, source))
+                .join(", ");
+            if sources.is_empty() {
+                println!("Card {}: {} copy, the original", id, copies);
+            } else {
+                println!("Card {}: {} copies, the original and {}", id, copies, sources);
+            }
         }
     }
+}
 
-    let part2: usize = cards_count.iter().sum();
-    println!("Part 2: {}", part2);
+#[cfg(test)]
+const EXAMPLE: [&str; 6] = [
+    "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+    "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+    "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+    "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+    "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+    "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+];
+
+#[test]
+fn day4_example() {
+    let cards: Vec<Card> = EXAMPLE.iter().map(|line| line.parse().unwrap()).collect();
+    assert!(validate(&cards).is_empty());
+
+    let cards = cards.iter().map(|card| (card.id, card.win_count())).collect_vec();
+    assert!(cards.iter().map(|(_, win_count)| *win_count).collect_vec() == [4, 2, 2, 1, 0, 0]);
+
+    let cascade = cascade(&cards, Overflow::Error).unwrap();
+    assert!(cascade.copies == [1, 2, 4, 8, 14, 1]);
+    assert!(cascade.received_from[3] == [(1, 1), (2, 2), (3, 4)]);
+    assert!(cascade.received_from[5].is_empty());
+}
+
+#[test]
+fn day4_overflow() {
+    let cards = ["Card 7: 1 2 3 | 1 2 4", "Card 8: 1 2 3 | 1 2 3"]
+        .map(|line| line.parse::<Card>().unwrap())
+        .map(|card| (card.id, card.win_count()));
+    assert!(cards == [(7, 2), (8, 3)]);
+
+    let clamped = cascade(&cards, Overflow::Clamp).unwrap();
+    assert!(clamped.copies == [1, 2]);
+    assert!(clamped.received_from[1] == [(7, 1)]);
+
+    assert!(cascade(&cards, Overflow::Error).err() == Some(OverflowError { card: 7, win_count: 2, remaining: 1 }));
+}
+
+#[test]
+fn day4_validation() {
+    let cards: Vec<Card> = [
+        "Card 1: 5 5 7 | 5 8 8 9 9",
+        "Card 3: 1 2 | 3 4",
+        "Card   4: 1 | 1",
+    ].iter().map(|line| line.parse().unwrap()).collect();
+
+    // the duplicated winning 5 counts once
+    assert!(cards[0].win_count() == 1);
+    assert!(validate(&cards) == [
+        CardIssue::DuplicateWinning { card: 1, number: 5 },
+        CardIssue::DuplicateHeld { card: 1, number: 8 },
+        CardIssue::DuplicateHeld { card: 1, number: 9 },
+        CardIssue::NonSequentialId { expected: 2, found: 3 },
+        CardIssue::NonSequentialId { expected: 3, found: 4 },
+    ]);
+
+    assert!(matches!("Card 1: 1 2 3 1 2".parse::<Card>(), Err(ParseCardError::MissingSeparator)));
+    assert!(matches!("1 2 | 3".parse::<Card>(), Err(ParseCardError::MissingId)));
+    assert!(matches!("Game 1: 1 | 2".parse::<Card>(), Err(ParseCardError::MissingId)));
+    assert!(matches!("Card 1: 1 x | 2".parse::<Card>(), Err(ParseCardError::Int(_))));
+}
+
+#[test]
+fn day4_bad_lines() {
+    let lines = ["Card 1: 1 2 | 2 3", "Card 2 1 | 1", "Card 3: 4 | 4", "Card 4: 1 2 3"];
+    let (cards, bad_lines) = parse_cards(lines.into_iter());
+    assert!(cards.iter().map(|card| card.id).collect_vec() == [1, 3]);
+    assert!(bad_lines == [
+        BadLine { line_number: 2, error: ParseCardError::MissingId },
+        BadLine { line_number: 4, error: ParseCardError::MissingSeparator },
+    ]);
+    assert!(bad_lines[1].to_string() == "Line 4: Winning and held numbers should be separated with '|'");
+    assert!(validate(&cards) == [CardIssue::NonSequentialId { expected: 2, found: 3 }]);
 }
@@ -1,6 +1,5 @@
-#![feature(is_sorted)]
-
 use aoc2023_rust::*;
+use aoc2023_rust::progress::Progress;
 use itertools::Itertools;
 use scan_fmt::scan_fmt;
 use std::ops::Range;
@@ -77,7 +76,7 @@ impl RangeMapper {
     }
 }
 
-fn parse_range_mapper(lines: &[String]) -> RangeMapper {
+fn parse_range_mapper(lines: &[&str]) -> RangeMapper {
     assert!(lines[0].contains(':'));
 
     RangeMapper(
@@ -94,8 +93,8 @@ fn parse_range_mapper(lines: &[String]) -> RangeMapper {
     )
 }
 
-fn main() {
-    let lines = read_input_lines("input/day5.txt");
+/// Initial values from the first line, and the mappers in order
+fn parse_almanac(lines: &[&str]) -> (Vec<i64>, Vec<RangeMapper>) {
     let initial_values: Vec<i64> = lines[0]
         .strip_prefix("seeds: ").unwrap()
         .split(' ')
@@ -109,17 +108,21 @@ fn main() {
         .map(parse_range_mapper)
         .collect();
 
-    let part1 = initial_values
+    (initial_values, groups)
+}
+
+fn part1(initial_values: &[i64], groups: &[RangeMapper]) -> i64 {
+    initial_values
         .iter()
         .map(|&v| groups.iter().fold(v, |v, g| g.map_value(v)))
-        .min().unwrap();
-
-    println!("Part 1: {}", part1);
+        .min().unwrap()
+}
 
+fn part2(initial_values: &[i64], groups: &[RangeMapper]) -> i64 {
     let initial_ranges: Vec<Range<i64>> = initial_values
-        .into_iter()
+        .iter()
         .tuples()
-        .map(|(start, size)| start..(start+size))
+        .map(|(&start, &size)| start..(start+size))
         .collect();
 
     let mapped_ranges = groups
@@ -129,11 +132,107 @@ fn main() {
             ranges.iter().flat_map(|r| mapper.map_range(r)).collect()
         });
 
-    let part2 = mapped_ranges
+    mapped_ranges
         .iter()
         .map(|r| r.start)
-        .min().unwrap();
+        .min().unwrap()
+}
+
+/// Part 2 by mapping every single value of the ranges, slow for the puzzle input
+fn part2_brute_force(initial_values: &[i64], groups: &[RangeMapper], progress: &Progress) -> i64 {
+    const CHUNK: i64 = 1 << 16;
 
-    println!("Part 2: {}", part2);
+    let initial_ranges: Vec<Range<i64>> = initial_values
+        .iter()
+        .tuples()
+        .map(|(&start, &size)| start..(start+size))
+        .collect();
+    progress.set_total(initial_ranges.iter().map(|r| (r.end - r.start) as u64).sum());
+
+    let mut lowest = i64::MAX;
+    for (idx, range) in initial_ranges.iter().enumerate() {
+        progress.message(format!("range {} of {}", idx + 1, initial_ranges.len()));
+        for chunk_start in range.clone().step_by(CHUNK as usize) {
+            let chunk = chunk_start..(chunk_start + CHUNK).min(range.end);
+            progress.increment((chunk.end - chunk.start) as u64);
+            let chunk_lowest = chunk.map(|v| groups.iter().fold(v, |v, g| g.map_value(v))).min().unwrap();
+            lowest = lowest.min(chunk_lowest);
+        }
+    }
+    lowest
 }
 
+fn main() {
+    // `--brute-force` checks part 2 by mapping every value, `--quiet` hides its progress
+    let input = read_input("input/day5.txt");
+    let (initial_values, groups) = parse_almanac(&input.lines().collect_vec());
+
+    println!("Part 1: {}", part1(&initial_values, &groups));
+    println!("Part 2: {}", part2(&initial_values, &groups));
+
+    if std::env::args().any(|arg| arg == "--brute-force") {
+        let progress = Progress::from_args(0);
+        println!("Part 2 by brute force: {}", part2_brute_force(&initial_values, &groups, &progress));
+    }
+}
+
+/// Maps every single seed, looking the value up in every line of a map
+#[cfg(test)]
+mod reference {
+    use itertools::Itertools;
+
+    fn numbers(line: &str) -> Vec<i64> {
+        line.split_whitespace().filter_map(|n| n.parse().ok()).collect()
+    }
+
+    fn location(seed: i64, lines: &[&str]) -> i64 {
+        let mut value = seed;
+        for map in lines.split(|line| line.is_empty()).skip(1) {
+            let mapped = map[1..].iter().map(|line| numbers(line)).find_map(|range| match range[..] {
+                [destination, source, length] if (source..source + length).contains(&value) => Some(destination + value - source),
+                _ => None
+            });
+            value = mapped.unwrap_or(value);
+        }
+        value
+    }
+
+    pub fn part1(lines: &[&str]) -> i64 {
+        numbers(lines[0]).iter().map(|&seed| location(seed, lines)).min().unwrap()
+    }
+
+    pub fn part2(lines: &[&str]) -> i64 {
+        numbers(lines[0])
+            .iter()
+            .tuples()
+            .flat_map(|(&start, &size)| start..start + size)
+            .map(|seed| location(seed, lines))
+            .min()
+            .unwrap()
+    }
+}
+
+#[test]
+fn day5_against_reference() {
+    use aoc2023_rust::differential::{assert_agree, cases};
+    use aoc2023_rust::generate::Day5;
+
+
+    let generator = Day5 { seed_ranges: 4, max_seed_range: 30, maps: 4, ranges_per_map: 6, max_value: 100 };
+    let solve = |text: &str| {
+        let (initial_values, groups) = parse_almanac(&text.lines().collect_vec());
+        (part1(&initial_values, &groups), part2(&initial_values, &groups))
+    };
+    let reference = |text: &str| {
+        let lines = text.lines().collect_vec();
+        (reference::part1(&lines), reference::part2(&lines))
+    };
+    assert_agree(generator.clone(), 300, reference, solve);
+
+    for case in cases(generator, 20) {
+        let (initial_values, groups) = parse_almanac(&case.text().lines().collect_vec());
+        let progress = Progress::hidden(0);
+        assert!(part2_brute_force(&initial_values, &groups, &progress) == part2(&initial_values, &groups));
+        assert!(progress.position() == initial_values.iter().skip(1).step_by(2).sum::<i64>() as u64);
+    }
+}
@@ -1,4 +1,4 @@
-use aoc2023_rust::read_input_lines;
+use aoc2023_rust::read_input;
 use itertools::Itertools;
 
 fn parse_list(s: &str) -> Vec<f64> {
@@ -24,16 +24,11 @@ fn quadratic_integral_solutions_count(a: f64, b: f64, c: f64) -> u64 {
     (x2.ceil() - x1.floor() - 1.) as u64 
 }
 
-fn main() {
-    let lines = read_input_lines("input/day6.txt");
-    
-    let line1 = lines[0].strip_prefix("Time:").unwrap();
-    let line2 = lines[1].strip_prefix("Distance:").unwrap();
-    
-    let times: Vec<f64> = parse_list(line1);
-    let distances: Vec<f64> = parse_list(line2);
-
-    let part1: u64 = times.iter().zip(distances.iter())
+fn part1(lines: &[&str]) -> u64 {
+    let times: Vec<f64> = parse_list(lines[0].strip_prefix("Time:").unwrap());
+    let distances: Vec<f64> = parse_list(lines[1].strip_prefix("Distance:").unwrap());
+
+    times.iter().zip(distances.iter())
         .map(|(&time, &distance)| {
             // x * (t - x) > distance
             // -xx + xt - dist > 0
@@ -41,17 +36,67 @@ fn main() {
             let b = time;
             let c = -distance;
             quadratic_integral_solutions_count(a, b, c)
-    }).product();
-
-    println!("Part 1: {}", part1);
+    }).product()
+}
 
+fn part2(lines: &[&str]) -> u64 {
     let parse_line_as_number = |line: &str| {
         line.chars().filter(|c|c.is_ascii_digit()).join("").parse().unwrap()
     };
 
-    let time2 = parse_line_as_number(line1);
-    let distance2 = parse_line_as_number(line2);
+    let time2 = parse_line_as_number(lines[0]);
+    let distance2 = parse_line_as_number(lines[1]);
+
+    quadratic_integral_solutions_count(-1., time2, -distance2)
+}
+
+fn main() {
+    let input = read_input("input/day6.txt");
+    let lines = input.lines().collect_vec();
+
+    println!("Part 1: {}", part1(&lines));
+    println!("Part 2: {}", part2(&lines));
+}
+
+/// Tries every time of holding the button
+#[cfg(test)]
+mod reference {
+    fn ways_to_win(time: u64, distance: u64) -> u64 {
+        (0..=time).filter(|hold| hold * (time - hold) > distance).count() as u64
+    }
+
+    fn numbers(line: &str) -> Vec<u64> {
+        line.split_whitespace().skip(1).map(|n| n.parse().unwrap()).collect()
+    }
+
+    pub fn part1(lines: &[&str]) -> u64 {
+        let (times, distances) = (numbers(lines[0]), numbers(lines[1]));
+        times.iter().zip(&distances).map(|(&time, &distance)| ways_to_win(time, distance)).product()
+    }
+
+    pub fn part2(lines: &[&str]) -> u64 {
+        let [time, distance] = [lines[0], lines[1]].map(|line| numbers(line).iter().map(u64::to_string).collect::<String>().parse().unwrap());
+        ways_to_win(time, distance)
+    }
+}
+
+#[test]
+fn day6_example() {
+    let lines = ["Time:      7  15   30", "Distance:  9  40  200"];
+    assert!(part1(&lines) == 288);
+    assert!(part2(&lines) == 71503);
+}
+
+#[test]
+fn day6_against_reference() {
+    use aoc2023_rust::differential::assert_agree;
+    use aoc2023_rust::generate::Day6;
+
+
+    let generator = Day6 { races: 3, max_time: 60 };
+    assert_agree(generator, 300, |text| reference::part1(&text.lines().collect_vec()), |text| part1(&text.lines().collect_vec()));
 
-    let part2 = quadratic_integral_solutions_count(-1., time2, -distance2);
-    println!("Part 2: {}", part2);
+    // the numbers are joined for part 2, two races keep it small enough to try every time
+    let generator = Day6 { races: 2, max_time: 300 };
+    assert_agree(generator, 100, |text| reference::part2(&text.lines().collect_vec()), |text| part2(&text.lines().collect_vec()));
 }
@@ -1,9 +1,14 @@
+use std::collections::BTreeMap;
 use std::num::ParseIntError;
-use aoc2023_rust::read_input_lines;
+use aoc2023_rust::{flag_value, read_input};
 use itertools::Itertools;
 use thiserror::Error;
 
-#[derive(PartialEq, PartialOrd, Eq, Ord)]
+const CARD_ORDER: &str = "$23456789TJQKA";
+const JOKER: u8 = 0; // position of '$'
+const JACK: u8 = 10; // position of 'J'
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Debug)]
 enum HandKind {
     HighCard = 0,
     Pair = 1,
@@ -33,9 +38,6 @@ impl std::str::FromStr for HandOfCards {
     type Err = ParseHandError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        const CARD_ORDER: &str = "$23456789TJQKA";
-        const JOKER: u8 = 0; // position of '$'
-        
         let s: [char; 5] = s
             .chars()
             .collect::<Vec<_>>()
@@ -52,40 +54,56 @@ impl std::str::FromStr for HandOfCards {
                 .chars()
                 .position(|card| c == card).unwrap() as u8);
 
-        let mut counts_map = cards
-            .iter()
-            .counts();
-
-        let joker_count = *counts_map.get(&JOKER).unwrap_or(&0);
-        counts_map.remove(&JOKER);
-
-        let mut counts = counts_map
-            .values()
-            .cloned()
-            .sorted_by(|x1, x2| x2.cmp(x1)) // decreasingly
-            .collect_vec(); 
-
-        if counts.is_empty() { counts.push(0); } // if only jokers
-        counts[0] += joker_count;
-
-        let kind = match counts.as_slice() {
-            [5] => HandKind::FiveOfKind,
-            [4, 1] => HandKind::FourOfKind,
-            [3, 2] => HandKind::FullHouse,
-            [3, 1, 1] => HandKind::ThreeOfKind,
-            [2, 2, 1] => HandKind::TwoPairs,
-            [2, ..] => HandKind::Pair,
-            [1, ..] => HandKind::HighCard,
-            _ => unreachable!()
-        };
+        Ok(HandOfCards { kind: kind_of(&cards), cards })
+    }
+}
+
+impl HandOfCards {
+    /// The same hand with its jacks played as jokers
+    fn with_jokers(&self) -> HandOfCards {
+        let cards = self.cards.map(|card| if card == JACK { JOKER } else { card });
+        HandOfCards { kind: kind_of(&cards), cards }
+    }
+}
+
+/// Kind of a hand, jokers count as the most common other card
+fn kind_of(cards: &[u8; 5]) -> HandKind {
+    let mut counts_map = cards
+        .iter()
+        .counts();
+
+    let joker_count = *counts_map.get(&JOKER).unwrap_or(&0);
+    counts_map.remove(&JOKER);
+
+    let mut counts = counts_map
+        .values()
+        .cloned()
+        .sorted_by(|x1, x2| x2.cmp(x1)) // decreasingly
+        .collect_vec(); 
+
+    if counts.is_empty() { counts.push(0); } // if only jokers
+    counts[0] += joker_count;
+
+    kind_from_counts(&counts)
+}
 
-        Ok(HandOfCards {kind, cards})
+/// Kind of a hand, given counts of the same cards sorted decreasingly
+fn kind_from_counts(counts: &[usize]) -> HandKind {
+    match counts {
+        [5] => HandKind::FiveOfKind,
+        [4, 1] => HandKind::FourOfKind,
+        [3, 2] => HandKind::FullHouse,
+        [3, 1, 1] => HandKind::ThreeOfKind,
+        [2, 2, 1] => HandKind::TwoPairs,
+        [2, ..] => HandKind::Pair,
+        [1, ..] => HandKind::HighCard,
+        _ => unreachable!()
     }
 }
 
 
-struct Play {
-    hand: HandOfCards,
+struct Play<H = HandOfCards> {
+    hand: H,
     bid: u32
 }
 
@@ -113,29 +131,380 @@ impl std::str::FromStr for Play {
     }
 }
 
-fn solve(lines: &[String]) -> u32 {
-    let mut plays: Vec<Play> = lines
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Suit {
+    Spades,
+    Hearts,
+    Diamonds,
+    Clubs
+}
+
+/// A card from a standard deck, rank uses the same values as `HandOfCards`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct PlayingCard {
+    rank: u8,
+    suit: Suit
+}
+
+#[derive(Error, Debug)]
+enum ParseCardError {
+    #[error("Card should be a rank followed by a suit, e.g. \"TS\"")]
+    WrongCardSize,
+
+    #[error("One of characters does not represent a card rank")]
+    UnexpectedRank,
+
+    #[error("One of characters does not represent a card suit")]
+    UnexpectedSuit
+}
+
+impl std::str::FromStr for PlayingCard {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [rank, suit]: [char; 2] = s
+            .chars()
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| ParseCardError::WrongCardSize)?;
+
+        // there are no jokers in a standard deck
+        let rank = CARD_ORDER
+            .chars()
+            .position(|card| card == rank.to_ascii_uppercase())
+            .filter(|&position| position as u8 != JOKER)
+            .ok_or(ParseCardError::UnexpectedRank)? as u8;
+
+        let suit = match suit.to_ascii_uppercase() {
+            'S' | '♠' => Suit::Spades,
+            'H' | '♥' => Suit::Hearts,
+            'D' | '♦' => Suit::Diamonds,
+            'C' | '♣' => Suit::Clubs,
+            _ => return Err(ParseCardError::UnexpectedSuit)
+        };
+
+        Ok(PlayingCard { rank, suit })
+    }
+}
+
+#[derive(PartialEq, PartialOrd, Eq, Ord, Debug)]
+enum PokerHandKind {
+    HighCard = 0,
+    Pair = 1,
+    TwoPairs = 2,
+    ThreeOfKind = 3,
+    Straight = 4,
+    Flush = 5,
+    FullHouse = 6,
+    FourOfKind = 7,
+    StraightFlush = 8
+}
+
+/// Standard poker hand of 5 cards.
+/// Ranks are ordered for comparison: the biggest groups first, then kickers, e.g.
+/// `7 7 7 K 2` for three sevens. Ace in `A 2 3 4 5` straight counts as the lowest card.
+#[derive(PartialEq, PartialOrd, Eq, Ord, Debug)]
+struct PokerHand {
+    kind: PokerHandKind,
+    ranks: [u8; 5]
+}
+
+const ACE: u8 = 13; // position of 'A'
+const LOW_ACE: u8 = 0; // below '2', used only for the A-2-3-4-5 straight
+
+impl PokerHand {
+    fn from_cards(cards: [PlayingCard; 5]) -> Self {
+        // (rank, count) sorted by count, then by rank, decreasingly
+        let groups = cards
+            .iter()
+            .map(|card| card.rank)
+            .counts()
+            .into_iter()
+            .sorted_by(|(r1, c1), (r2, c2)| (c2, r2).cmp(&(c1, r1)))
+            .collect_vec();
+
+        let counts = groups.iter().map(|&(_, count)| count).collect_vec();
+        let mut ranks: [u8; 5] = groups
+            .iter()
+            .flat_map(|&(rank, count)| std::iter::repeat_n(rank, count))
+            .collect_vec()
+            .try_into()
+            .unwrap();
+
+        let is_flush = cards.iter().map(|card| card.suit).all_equal();
+        let is_wheel = ranks == [ACE, 4, 3, 2, 1];
+        let is_straight = counts.len() == 5 && (ranks[0] - ranks[4] == 4 || is_wheel);
+
+        if is_wheel {
+            ranks = [4, 3, 2, 1, LOW_ACE];
+        }
+
+        let kind = match (is_straight, is_flush, kind_from_counts(&counts)) {
+            (true, true, _) => PokerHandKind::StraightFlush,
+            (_, _, HandKind::FourOfKind) => PokerHandKind::FourOfKind,
+            (_, _, HandKind::FullHouse) => PokerHandKind::FullHouse,
+            (_, true, _) => PokerHandKind::Flush,
+            (true, _, _) => PokerHandKind::Straight,
+            (_, _, HandKind::ThreeOfKind) => PokerHandKind::ThreeOfKind,
+            (_, _, HandKind::TwoPairs) => PokerHandKind::TwoPairs,
+            (_, _, HandKind::Pair) => PokerHandKind::Pair,
+            (_, _, HandKind::HighCard) => PokerHandKind::HighCard,
+            (_, _, HandKind::FiveOfKind) => unreachable!("Cards should be unique")
+        };
+
+        PokerHand { kind, ranks }
+    }
+}
+
+/// The best 5-card hand that can be chosen from the cards, e.g. out of 7 in Texas Hold'em.
+/// Returns None if there are less than 5 cards.
+fn best_poker_hand(cards: &[PlayingCard]) -> Option<PokerHand> {
+    cards
+        .iter()
+        .cloned()
+        .combinations(5)
+        .map(|hand| PokerHand::from_cards(hand.try_into().unwrap()))
+        .max()
+}
+
+#[derive(Error, Debug)]
+enum ParsePokerHandError {
+    #[error(transparent)]
+    Card(#[from] ParseCardError),
+
+    #[error("Hand has less than 5 cards")]
+    NotEnoughCards,
+
+    #[error("The same card appears more than once")]
+    DuplicateCard
+}
+
+impl std::str::FromStr for PokerHand {
+    type Err = ParsePokerHandError;
+
+    /// Parses a space separated list of at least 5 cards, e.g. "AS KD 7H 7C 2S"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards: Vec<PlayingCard> = s
+            .split_whitespace()
+            .map(|card| card.parse())
+            .try_collect()?;
+
+        if !cards.iter().all_unique() {
+            return Err(ParsePokerHandError::DuplicateCard);
+        }
+
+        best_poker_hand(&cards).ok_or(ParsePokerHandError::NotEnoughCards)
+    }
+}
+
+#[derive(Error, Debug)]
+enum ParsePokerPlayError {
+    #[error(transparent)]
+    Hand(#[from] ParsePokerHandError),
+
+    #[error(transparent)]
+    Int(#[from] ParseIntError),
+
+    #[error("Could not split play into cards and a bid")]
+    Split
+}
+
+impl std::str::FromStr for Play<PokerHand> {
+    type Err = ParsePokerPlayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // "AS KD 7H 7C 2S 765", bid is the last element
+        let (l, r) = s.rsplit_once(' ').ok_or(ParsePokerPlayError::Split)?;
+        Ok(Play {
+            hand: l.parse()?,
+            bid: r.parse()?
+        })
+    }
+}
+
+fn parse_plays<H>(lines: &[&str]) -> Vec<Play<H>>
+    where Play<H>: std::str::FromStr,
+          <Play<H> as std::str::FromStr>::Err: std::fmt::Debug
+{
+    lines
         .iter()
         .map(|line| line.parse().unwrap())
-        .collect();
+        .collect()
+}
 
+/// Winnings (bid * rank) of each play, in the order of the plays
+fn winnings<H: Ord>(plays: &[Play<H>]) -> Vec<u32> {
+    let mut ranked = (0..plays.len()).collect_vec();
     // sort by key has issues with lifetime, as its lambda returns by value
-    plays.sort_by(|p1, p2| p1.hand.cmp(&p2.hand));
+    ranked.sort_by(|&i1, &i2| plays[i1].hand.cmp(&plays[i2].hand));
+
+    let mut result = vec![0; plays.len()];
+    for (idx, &play_idx) in ranked.iter().enumerate() {
+        result[play_idx] = plays[play_idx].bid * (idx as u32 + 1);
+    }
+    result
+}
+
+/// The same plays with jacks played as jokers
+fn with_jokers(plays: &[Play]) -> Vec<Play> {
+    plays.iter().map(|play| Play { hand: play.hand.with_jokers(), bid: play.bid }).collect()
+}
 
-    plays
+/// Statistics of hand kinds, without and with jokers
+struct JokerReport {
+    kinds: BTreeMap<HandKind, usize>,
+    kinds_with_jokers: BTreeMap<HandKind, usize>,
+    // (kind without jokers, kind with jokers) -> number of hands, only for changed kinds
+    promotions: BTreeMap<(HandKind, HandKind), usize>,
+    // sum of winnings of all hands of given kind
+    winnings: BTreeMap<HandKind, u32>,
+    winnings_with_jokers: BTreeMap<HandKind, u32>,
+}
+
+fn joker_report(plays: &[Play]) -> JokerReport {
+    let plays_with_jokers = with_jokers(plays);
+
+    let sum_by_kind = |plays: &[Play]| -> BTreeMap<HandKind, u32> {
+        let mut sums = BTreeMap::new();
+        for (play, winning) in plays.iter().zip(winnings(plays)) {
+            *sums.entry(play.hand.kind).or_default() += winning;
+        }
+        sums
+    };
+
+    let promotions = plays
         .iter()
-        .enumerate()
-        .map(|(idx, play)| play.bid * (idx as u32 + 1))
-        .sum()
+        .zip(plays_with_jokers.iter())
+        .map(|(p1, p2)| (p1.hand.kind, p2.hand.kind))
+        .filter(|(k1, k2)| k1 != k2)
+        .counts()
+        .into_iter()
+        .collect();
+
+    JokerReport {
+        kinds: plays.iter().map(|play| play.hand.kind).counts().into_iter().collect(),
+        kinds_with_jokers: plays_with_jokers.iter().map(|play| play.hand.kind).counts().into_iter().collect(),
+        promotions,
+        winnings: sum_by_kind(plays),
+        winnings_with_jokers: sum_by_kind(&plays_with_jokers),
+    }
+}
+
+impl std::fmt::Display for JokerReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<12} {:>8} {:>12} {:>8} {:>12}",
+                 "Kind", "Hands", "Winnings", "Jokers", "Winnings")?;
+
+        let all_kinds = self.kinds.keys().chain(self.kinds_with_jokers.keys()).sorted().dedup();
+        for kind in all_kinds {
+            writeln!(f, "{:<12} {:>8} {:>12} {:>8} {:>12}",
+                     format!("{kind:?}"),
+                     self.kinds.get(kind).unwrap_or(&0),
+                     self.winnings.get(kind).unwrap_or(&0),
+                     self.kinds_with_jokers.get(kind).unwrap_or(&0),
+                     self.winnings_with_jokers.get(kind).unwrap_or(&0))?;
+        }
+
+        writeln!(f, "Promoted by jokers:")?;
+        for ((from, to), count) in &self.promotions {
+            writeln!(f, "  {from:?} -> {to:?}: {count}")?;
+        }
+        Ok(())
+    }
 }
 
 fn main() {
-    let mut lines = read_input_lines("input/day7.txt");
+    // `day7 --poker <path>` ranks standard poker hands instead of Camel Cards
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(poker_input) = flag_value(&args, "--poker") {
+        let plays = parse_plays::<PokerHand>(&read_input(poker_input).lines().collect_vec());
+        let total: u32 = winnings(&plays).iter().sum();
+        println!("Poker: {}", total);
+        return;
+    }
+
+    // both parts and the report use the same parsed plays
+    let plays = parse_plays::<HandOfCards>(&read_input("input/day7.txt").lines().collect_vec());
 
-    let part1 = solve(&lines);
+    let part1: u32 = winnings(&plays).iter().sum();
     println!("Part 1: {}", part1);
 
-    lines.iter_mut().for_each(|s| *s = s.replace('J', "$"));
-    let part2 = solve(&lines);
+    let part2: u32 = winnings(&with_jokers(&plays)).iter().sum();
     println!("Part 2: {}", part2);
+
+    if args.iter().any(|arg| arg == "--report") {
+        print!("{}", joker_report(&plays));
+    }
+}
+
+#[cfg(test)]
+fn poker(s: &str) -> PokerHand {
+    s.parse().unwrap()
+}
+
+#[test]
+fn day7_poker_hand_kinds() {
+    assert!(poker("AS KS QS JS TS").kind == PokerHandKind::StraightFlush);
+    assert!(poker("9C 9D 9H 9S 2D").kind == PokerHandKind::FourOfKind);
+    assert!(poker("3C 3D 3H 8S 8D").kind == PokerHandKind::FullHouse);
+    assert!(poker("2H 7H 9H JH KH").kind == PokerHandKind::Flush);
+    assert!(poker("AD 2C 3H 4S 5D").kind == PokerHandKind::Straight);
+    assert!(poker("TD JC QH KS AD").kind == PokerHandKind::Straight);
+    assert!(poker("QD QC QH 4S 5D").kind == PokerHandKind::ThreeOfKind);
+    assert!(poker("QD QC 4H 4S 5D").kind == PokerHandKind::TwoPairs);
+    assert!(poker("QD QC 3H 4S 5D").kind == PokerHandKind::Pair);
+    assert!(poker("QD 2C 3H 4S 5D").kind == PokerHandKind::HighCard);
+    assert!(poker("QD KC AH 2S 3D").kind == PokerHandKind::HighCard);
+}
+
+#[test]
+fn day7_poker_hand_ranking() {
+    assert!(poker("AS KS QS JS TS") > poker("9H 8H 7H 6H 5H"));
+    assert!(poker("6H 5H 4H 3H 2H") > poker("5D 4D 3D 2D AD"));
+    assert!(poker("6D 5C 4H 3S 2D") > poker("5D 4C 3H 2S AD"));
+    assert!(poker("2C 2D 2H 2S 3D") > poker("AC AD AH KS KD"));
+    assert!(poker("AC AD AH 2S 2D") > poker("KC KD KH QS QD"));
+    assert!(poker("2H 3H 4H 5H 7H") > poker("AC KD QH JS TD"));
+    assert!(poker("AD 2C 3H 4S 5D") > poker("AC AD AH KS QD"));
+
+    // kickers
+    assert!(poker("AC AD KH 4S 3D") > poker("AH AS QH JS TD"));
+    assert!(poker("KC KD 4H 4S 3D") > poker("KH KS 4C 4D 2D"));
+    assert!(poker("KC KD 5H 4S 3D") > poker("KH KS 5C 4D 2D"));
+    assert!(poker("AH KH 9H 5H 3H") > poker("AC KC 9C 5C 2C"));
+    assert!(poker("AC AD KH 4S 3D") == poker("AH AS KC 4D 3C"));
+}
+
+#[test]
+fn day7_poker_best_of_seven() {
+    // Texas Hold'em: 2 hole cards and 5 on the board
+    assert!(poker("AH KH QH JH 2C 3D TH") == poker("AH KH QH JH TH"));
+    assert!(poker("2H 2C 2D 5S 5C 9H 9D") == poker("2H 2C 2D 9H 9D"));
+    assert!(poker("AD 2C 3H 4S 5D 6C KH").kind == PokerHandKind::Straight);
+    assert!(poker("AD 2C 3H 4S 5D 6C KH").ranks == [5, 4, 3, 2, 1]);
+    assert!(poker("AC AD KH 4S 3D 2C 7H").ranks == [ACE, ACE, 12, 6, 3]);
+}
+
+#[test]
+fn day7_poker_parse_errors() {
+    assert!(matches!("AS KS QS JS".parse::<PokerHand>(), Err(ParsePokerHandError::NotEnoughCards)));
+    assert!(matches!("AS AS QS JS TS".parse::<PokerHand>(), Err(ParsePokerHandError::DuplicateCard)));
+    assert!(matches!("AS KS QS JS 1S".parse::<PokerHand>(), Err(ParsePokerHandError::Card(_))));
+    assert!(matches!("AS KS QS JS $S".parse::<PokerHand>(), Err(ParsePokerHandError::Card(_))));
+}
+
+#[test]
+fn day7_joker_report_example() {
+    let lines = ["32T3K 765", "T55J5 684", "KK677 28", "KTJJT 220", "QQQJA 483"];
+    let plays = parse_plays::<HandOfCards>(&lines);
+    let report = joker_report(&plays);
+
+    assert!(report.winnings.values().sum::<u32>() == 6440);
+    assert!(report.winnings_with_jokers.values().sum::<u32>() == 5905);
+    assert!(report.kinds[&HandKind::TwoPairs] == 2);
+    assert!(report.kinds_with_jokers[&HandKind::FourOfKind] == 3);
+    assert!(report.promotions == BTreeMap::from([
+        ((HandKind::TwoPairs, HandKind::FourOfKind), 1),
+        ((HandKind::ThreeOfKind, HandKind::FourOfKind), 2),
+    ]));
 }
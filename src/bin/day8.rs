@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use aoc2023_rust::read_input_lines;
+use aoc2023_rust::read_input;
+use aoc2023_rust::progress::Progress;
 use scan_fmt::scan_fmt;
 use simple_scan::IteratorSimpleScanExt;
 
@@ -31,33 +32,36 @@ fn get_position_sequence<'a>(
         })
 }
 
-fn main() {
-    let lines = read_input_lines("input/day8.txt");
-    let directions: &str = &lines[0];
-    let desert_map: HashMap<_, _> = lines
+fn parse_network(lines: &[&str]) -> HashMap<String, DesertCrossing> {
+    lines
         .iter()
         // Second line is empty, irst is for directions - already parsed above
         .skip(2)
         .map(|line| parse_line(line))
-        .collect();
+        .collect()
+}
 
+fn part1(directions: &str, desert_map: &HashMap<String, DesertCrossing>) -> usize {
     const START: &str = "AAA";
     const FINISH: &str = "ZZZ";
 
-    let part1 = get_position_sequence(directions, &desert_map, START)
+    get_position_sequence(directions, desert_map, START)
         .position(|x| x == FINISH)
-        .unwrap() + 1;
-
-    println!("Part 1: {}", part1);
+        .unwrap() + 1
+}
 
+/// Reports the progress per starting node
+fn part2(directions: &str, desert_map: &HashMap<String, DesertCrossing>, progress: &Progress) -> usize {
     let starting_nodes: Vec<_> = desert_map
         .keys()
         .filter(|node| node.ends_with('A'))
         .map(|s| s.as_str())
         .collect();
+    progress.set_total(starting_nodes.len() as u64);
 
     let get_cycle_length = |&start| -> usize {
-        let mut iter = get_position_sequence(directions, &desert_map, start);
+        progress.message(format!("walking from {}", start));
+        let mut iter = get_position_sequence(directions, desert_map, start);
 
         // Assumption: it will terminate
         let (end1, end2) = (
@@ -67,11 +71,63 @@ fn main() {
 
         assert!(end1 == end2);
         assert!(end1 % directions.len() == 0);
+        progress.increment(1);
         end1
     };
 
     let subcycles: Vec<_> = starting_nodes.iter().map(get_cycle_length).collect();
 
-    let part2 = subcycles.into_iter().reduce(num_integer::lcm).unwrap();
-    println!("Part 2: {}", part2);
+    subcycles.into_iter().reduce(num_integer::lcm).unwrap()
+}
+
+fn main() {
+    let input = read_input("input/day8.txt");
+    let lines: Vec<&str> = input.lines().collect();
+    let directions = lines[0];
+    let desert_map = parse_network(&lines);
+
+    println!("Part 1: {}", part1(directions, &desert_map));
+    // `--quiet` hides the progress of part 2
+    println!("Part 2: {}", part2(directions, &desert_map, &Progress::from_args(0)));
+}
+
+/// Walks all the ghosts at once, until all of them stand on Z nodes
+#[cfg(test)]
+mod reference {
+    use std::collections::HashMap;
+
+    pub fn part2(lines: &[&str]) -> usize {
+        let network: HashMap<&str, (&str, &str)> = lines[2..]
+            .iter()
+            .map(|line| (&line[0..3], (&line[7..10], &line[12..15])))
+            .collect();
+
+        let mut positions: Vec<&str> = network.keys().filter(|node| node.ends_with('A')).cloned().collect();
+        let mut steps = 0;
+        for direction in lines[0].chars().cycle() {
+            if positions.iter().all(|node| node.ends_with('Z')) {
+                break;
+            }
+            for position in positions.iter_mut() {
+                let (left, right) = network[position];
+                *position = if direction == 'L' { left } else { right };
+            }
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[test]
+fn day8_against_reference() {
+    use aoc2023_rust::differential::assert_agree;
+    use aoc2023_rust::generate::Day8;
+
+
+    let solve = |text: &str| {
+        let lines: Vec<&str> = text.lines().collect();
+        part2(lines[0], &parse_network(&lines), &Progress::hidden(0))
+    };
+    let generator = Day8 { directions: 3, ghosts: 3, max_rounds: 7 };
+    assert_agree(generator, 200, |text| reference::part2(&text.lines().collect::<Vec<_>>()), solve);
 }
@@ -1,38 +1,83 @@
-use aoc2023_rust::read_input_lines;
-use itertools::Itertools;
+use aoc2023_rust::read_input;
+use aoc2023_rust::sequence::PolynomialSequence;
+use num::BigInt;
 
-fn parse_line(l: &str) -> Vec<i32> {
+// big integers, the extrapolated values of long histories do not fit in any fixed width
+fn parse_line(l: &str) -> Vec<BigInt> {
     l.split(' ').map(|s| s.parse().unwrap()).collect()
 }
 
-fn next_value(v: &[i32]) -> i32 {
-    if v.iter().all_equal() { v[0] }
-    else {
-        let next_layer: Vec<_> = v.iter()
-            .tuple_windows()
-            .map(|(a, b)| b - a)
-            .collect();
-        next_value(&next_layer) + v.last().unwrap()
-    }
+/// The lowest degree polynomial through all the values, even if the history is too short
+/// to tell that it is a polynomial
+fn fit_sequence(v: &[BigInt]) -> PolynomialSequence<BigInt> {
+    PolynomialSequence::interpolate(v).expect("History should not be empty")
+}
+
+fn next_value(v: &[BigInt]) -> BigInt {
+    fit_sequence(v).term(v.len() as i64).expect("Big integers should not overflow")
+}
+
+fn previous_value(v: &[BigInt]) -> BigInt {
+    fit_sequence(v).term(-1).expect("Big integers should not overflow")
 }
 
 fn main() {
-    let lines = read_input_lines("input/day9.txt");
-    let sequences: Vec<_> = lines.iter().map(|l| parse_line(l)).collect();
+    let input = read_input("input/day9.txt");
+    let sequences: Vec<_> = input.lines().map(parse_line).collect();
 
-    let part1: i32 = sequences.iter().map(|s| next_value(s)).sum();
-    println!("Part 1: {}", part1);
+    // histories too short to tell they are polynomials are only warned about, they are still extrapolated
+    for (line, sequence) in sequences.iter().enumerate() {
+        if let Err(err) = PolynomialSequence::fit(sequence) {
+            eprintln!("Line {}: {}", line + 1, err);
+        }
+    }
 
-    let mut sequences = sequences;
-    sequences.iter_mut().for_each(|seq| seq.reverse());
+    let part1: BigInt = sequences.iter().map(|s| next_value(s)).sum();
+    println!("Part 1: {}", part1);
 
-    let part2: i32 = sequences.iter().map(|s| next_value(s)).sum();
+    let part2: BigInt = sequences.iter().map(|s| previous_value(s)).sum();
     println!("Part 2: {}", part2);
+
+    if std::env::args().any(|arg| arg == "--polynomials") {
+        for (line, sequence) in sequences.iter().enumerate() {
+            let polynomial = fit_sequence(sequence).polynomial();
+            println!("{:>4}: degree {}, p(n) = {}", line + 1, polynomial.degree(), polynomial);
+        }
+    }
+}
+
+#[cfg(test)]
+fn history(values: &[i64]) -> Vec<BigInt> {
+    values.iter().map(|&v| BigInt::from(v)).collect()
 }
 
 #[test]
 fn day9_example() {
-    assert!(next_value(&[ 0,  3,  6,  9, 12, 15]) == 18);
-    assert!(next_value(&[ 1,  3,  6, 10, 15, 21]) == 28);
-    assert!(next_value(&[10, 13, 16, 21, 30, 45]) == 68);
+    assert!(next_value(&history(&[ 0,  3,  6,  9, 12, 15])) == 18.into());
+    assert!(next_value(&history(&[ 1,  3,  6, 10, 15, 21])) == 28.into());
+    assert!(next_value(&history(&[10, 13, 16, 21, 30, 45])) == 68.into());
+}
+
+#[test]
+fn day9_example_part2() {
+    assert!(previous_value(&history(&[ 0,  3,  6,  9, 12, 15])) == (-3).into());
+    assert!(previous_value(&history(&[ 1,  3,  6, 10, 15, 21])) == 0.into());
+    assert!(previous_value(&history(&[10, 13, 16, 21, 30, 45])) == 5.into());
+}
+
+#[test]
+fn day9_short_history() {
+    assert!(PolynomialSequence::fit(&history(&[1, 2])).is_err());
+    assert!(next_value(&history(&[1, 2])) == 3.into());
+    assert!(previous_value(&history(&[1, 2])) == 0.into());
+    assert!(next_value(&history(&[7])) == 7.into());
+}
+
+#[test]
+fn day9_beyond_i128() {
+    // 2^125 * n, the history fits in i128, but the next term is 2^127
+    let scale = BigInt::from(2).pow(125);
+    let values = (1..=3).map(|n: i32| &scale * n).collect::<Vec<_>>();
+    assert!(next_value(&values) == BigInt::from(2).pow(127));
+    assert!(next_value(&values) > BigInt::from(i128::MAX));
 }
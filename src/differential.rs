@@ -0,0 +1,121 @@
+//! Comparing solvers with simple reference implementations on many generated inputs.
+//! On a mismatch the input is shrunk as long as the two still disagree.
+//! Only for tests, built with the `differential` feature.
+
+use std::fmt::{self, Debug};
+
+use crate::generate::InputGenerator;
+
+/// Generated input, reproducible from the settings and the seed
+#[derive(Clone)]
+pub struct Case<G> {
+    pub generator: G,
+    pub seed: u64
+}
+
+impl<G: InputGenerator> Case<G> {
+    pub fn text(&self) -> String {
+        self.generator.generate_seeded(self.seed)
+    }
+
+    /// The same seed with each of the smaller settings
+    pub fn shrink(&self) -> Vec<Self> {
+        self.generator
+            .shrink()
+            .into_iter()
+            .map(|generator| Case { generator, seed: self.seed })
+            .collect()
+    }
+}
+
+impl<G: InputGenerator + Debug> Debug for Case<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} with seed {}:\n{}", self.generator, self.seed, self.text())
+    }
+}
+
+/// Cases for the seeds from 0 to `count - 1`, with the same settings
+pub fn cases<G: Clone>(generator: G, count: u64) -> impl Iterator<Item = Case<G>> {
+    (0..count).map(move |seed| Case { generator: generator.clone(), seed })
+}
+
+pub struct Mismatch<I, O> {
+    pub input: I,
+    pub expected: O,
+    pub actual: O
+}
+
+/// The first input where the solver disagrees with the reference, shrunk as long as they disagree.
+/// `shrink` lists smaller variants of an input, the first one still disagreeing is taken.
+pub fn find_mismatch<I, O: PartialEq>(
+        inputs: impl IntoIterator<Item = I>,
+        reference: impl Fn(&I) -> O,
+        solver: impl Fn(&I) -> O,
+        shrink: impl Fn(&I) -> Vec<I>) -> Option<Mismatch<I, O>> {
+    let disagreement = |input: &I| {
+        let (expected, actual) = (reference(input), solver(input));
+        (expected != actual).then_some((expected, actual))
+    };
+
+    let (mut input, (mut expected, mut actual)) = inputs
+        .into_iter()
+        .find_map(|input| disagreement(&input).map(|outputs| (input, outputs)))?;
+
+    while let Some((smaller, outputs)) = shrink(&input)
+            .into_iter()
+            .find_map(|smaller| disagreement(&smaller).map(|outputs| (smaller, outputs))) {
+        input = smaller;
+        (expected, actual) = outputs;
+    }
+
+    Some(Mismatch { input, expected, actual })
+}
+
+/// Panics with the shrunk case if the solver disagrees with the reference on the input of any seed below `count`.
+/// Both are given the input text.
+pub fn assert_agree<G, O>(generator: G, count: u64, reference: impl Fn(&str) -> O, solver: impl Fn(&str) -> O)
+    where G: InputGenerator + Clone + Debug,
+          O: PartialEq + Debug
+{
+    let mismatch = find_mismatch(
+        cases(generator, count),
+        |case| reference(&case.text()),
+        |case| solver(&case.text()),
+        Case::shrink);
+    if let Some(Mismatch { input, expected, actual }) = mismatch {
+        panic!("Solver returned {:?}, reference {:?}, for {:?}", actual, expected, input);
+    }
+}
+
+#[test]
+fn differential_shrinks_mismatch() {
+    // drops the values above 5
+    let solver = |values: &Vec<u32>| values.iter().filter(|&&v| v <= 5).sum::<u32>();
+    let reference = |values: &Vec<u32>| values.iter().sum::<u32>();
+    let shrink = |values: &Vec<u32>| {
+        let removed = (0..values.len()).map(|idx| [&values[..idx], &values[idx + 1..]].concat());
+        let decreased = (0..values.len()).filter(|&idx| values[idx] > 0).map(|idx| {
+            let mut smaller = values.clone();
+            smaller[idx] -= 1;
+            smaller
+        });
+        removed.chain(decreased).collect()
+    };
+
+    let inputs = vec![vec![1, 2, 3], vec![4, 9, 2, 8], vec![20]];
+    let mismatch = find_mismatch(inputs.clone(), reference, solver, shrink).unwrap();
+    assert!(mismatch.input == [6]);
+    assert!(mismatch.expected == 6 && mismatch.actual == 0);
+
+    assert!(find_mismatch(inputs[..1].to_vec(), reference, solver, shrink).is_none());
+}
+
+#[test]
+fn differential_case_shrink() {
+    use crate::generate::Day6;
+
+    let case = Case { generator: Day6 { races: 2, max_time: 10 }, seed: 7 };
+    let smaller = case.shrink();
+    assert!(smaller.iter().all(|smaller| smaller.seed == 7));
+    assert!(smaller.iter().map(|c| (c.generator.races, c.generator.max_time)).collect::<Vec<_>>() == [(1, 10), (2, 5), (2, 9)]);
+}
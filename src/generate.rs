@@ -0,0 +1,727 @@
+//! Random, valid puzzle inputs for stress tests and benchmarks.
+//! Each day has its own settings, the defaults give inputs of about the size of the real ones.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use ndarray::Array2;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::seq::index::sample;
+
+use crate::pipes::Connections;
+
+pub trait InputGenerator {
+    /// Input text, lines end with '\n'
+    fn generate(&self, rng: &mut StdRng) -> String;
+
+    /// The same seed gives the same input
+    fn generate_seeded(&self, seed: u64) -> String {
+        self.generate(&mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Settings a step smaller in each direction, to shrink inputs two solvers disagree on.
+    /// None by default, so the inputs are not shrunk.
+    fn shrink(&self) -> Vec<Self> where Self: Sized {
+        Vec::new()
+    }
+}
+
+fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Calibration lines with digits, number words and other letters, each with at least one digit
+#[derive(Clone, Debug)]
+pub struct Day1 {
+    pub lines: usize,
+    // lengths are drawn up to this, a word or the added digit may go past it
+    pub line_length: usize,
+    pub word_chance: f64,
+    pub digit_chance: f64
+}
+
+impl Default for Day1 {
+    fn default() -> Self {
+        Day1 { lines: 1000, line_length: 40, word_chance: 0.1, digit_chance: 0.05 }
+    }
+}
+
+impl InputGenerator for Day1 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+        join_lines((0..self.lines).map(|_| {
+            let mut line = String::new();
+            let length = rng.gen_range(1..=self.line_length.max(1));
+            while line.len() < length {
+                if rng.gen_bool(self.word_chance) {
+                    line += WORDS.choose(rng).unwrap();
+                } else if rng.gen_bool(self.digit_chance) {
+                    line.push(rng.gen_range(b'1'..=b'9') as char);
+                } else {
+                    line.push(rng.gen_range(b'a'..=b'z') as char);
+                }
+            }
+            line.insert(rng.gen_range(0..=line.len()), rng.gen_range(b'1'..=b'9') as char);
+            line
+        }))
+    }
+}
+
+/// Games with draws of a few cubes of each colour
+#[derive(Clone, Debug)]
+pub struct Day2 {
+    pub games: usize,
+    pub max_draws: usize,
+    pub max_cubes: u32,
+    pub colours: Vec<String>
+}
+
+impl Default for Day2 {
+    fn default() -> Self {
+        Day2 {
+            games: 100,
+            max_draws: 6,
+            max_cubes: 20,
+            colours: vec!["red".to_string(), "green".to_string(), "blue".to_string()]
+        }
+    }
+}
+
+impl InputGenerator for Day2 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        join_lines((1..=self.games).map(|id| {
+            let draws = (0..rng.gen_range(1..=self.max_draws.max(1)))
+                .map(|_| {
+                    let colours = rng.gen_range(1..=self.colours.len());
+                    self.colours
+                        .choose_multiple(rng, colours)
+                        .collect_vec()
+                        .into_iter()
+                        .map(|colour| format!("{} {}", rng.gen_range(1..=self.max_cubes), colour))
+                        .join(", ")
+                })
+                .join("; ");
+            format!("Game {}: {}", id, draws)
+        }))
+    }
+}
+
+/// Engine schematic, numbers of up to three digits and symbols scattered over '.'
+#[derive(Clone, Debug)]
+pub struct Day3 {
+    pub width: usize,
+    pub height: usize,
+    // chances of a cell starting a number or holding a symbol
+    pub number_density: f64,
+    pub symbol_density: f64,
+    pub symbols: Vec<char>
+}
+
+impl Default for Day3 {
+    fn default() -> Self {
+        Day3 {
+            width: 140,
+            height: 140,
+            number_density: 0.1,
+            symbol_density: 0.05,
+            symbols: "*#+$/@=%-&".chars().collect()
+        }
+    }
+}
+
+impl InputGenerator for Day3 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        join_lines((0..self.height).map(|_| {
+            let mut line = String::new();
+            while line.len() < self.width {
+                if rng.gen_bool(self.number_density) {
+                    let digits = rng.gen_range(1..=3).min(self.width - line.len());
+                    line.extend((0..digits).map(|_| rng.gen_range(b'0'..=b'9') as char));
+                    // numbers next to each other would merge into one
+                    if line.len() < self.width {
+                        line.push('.');
+                    }
+                } else if rng.gen_bool(self.symbol_density) {
+                    line.push(*self.symbols.choose(rng).unwrap());
+                } else {
+                    line.push('.');
+                }
+            }
+            line
+        }))
+    }
+}
+
+/// Scratchcards with distinct numbers, no card wins copies past the last card
+#[derive(Clone, Debug)]
+pub struct Day4 {
+    pub cards: usize,
+    pub winning: usize,
+    pub held: usize,
+    // numbers are from 1 to `max_number`, at least `winning + held` of them
+    pub max_number: usize,
+    // chance of each winning number to be held, with more matches the copies grow exponentially
+    pub match_chance: f64
+}
+
+impl Default for Day4 {
+    fn default() -> Self {
+        Day4 { cards: 200, winning: 10, held: 25, max_number: 99, match_chance: 0.1 }
+    }
+}
+
+impl InputGenerator for Day4 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        assert!(self.winning + self.held <= self.max_number, "Not enough numbers for distinct ones on a card");
+        let id_width = self.cards.to_string().len();
+        let number_width = self.max_number.to_string().len();
+        let format_numbers = |numbers: &[usize]| numbers.iter().map(|n| format!("{:>1$}", n, number_width)).join(" ");
+
+        join_lines((1..=self.cards).map(|id| {
+            let matches = (0..self.winning)
+                .filter(|_| rng.gen_bool(self.match_chance))
+                .count()
+                .min(self.held)
+                .min(self.cards - id);
+            let numbers = sample(rng, self.max_number, self.winning + self.held - matches)
+                .into_iter()
+                .map(|n| n + 1)
+                .collect_vec();
+
+            let winning = &numbers[..self.winning];
+            let mut held = [&winning[..matches], &numbers[self.winning..]].concat();
+            held.shuffle(rng);
+            format!("Card {:>3$}: {} | {}", id, format_numbers(winning), format_numbers(&held), id_width)
+        }))
+    }
+}
+
+/// Seed ranges and maps, each map moves around the pieces of `0..max_value`
+#[derive(Clone, Debug)]
+pub struct Day5 {
+    pub seed_ranges: usize,
+    pub max_seed_range: u64,
+    pub maps: usize,
+    pub ranges_per_map: usize,
+    pub max_value: u64
+}
+
+impl Default for Day5 {
+    fn default() -> Self {
+        Day5 { seed_ranges: 10, max_seed_range: 500_000_000, maps: 7, ranges_per_map: 30, max_value: u32::MAX as u64 }
+    }
+}
+
+impl InputGenerator for Day5 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        const CATEGORIES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+        let category = |idx: usize| CATEGORIES.get(idx).map_or(format!("category{}", idx), |name| name.to_string());
+
+        let seeds = (0..self.seed_ranges)
+            .map(|_| {
+                let start = rng.gen_range(0..self.max_value);
+                let size = rng.gen_range(1..=self.max_seed_range).min(self.max_value - start);
+                format!("{} {}", start, size)
+            })
+            .join(" ");
+        let mut lines = vec![format!("seeds: {}", seeds)];
+
+        for map in 0..self.maps {
+            // split the values into pieces, and put the pieces in a random order
+            let ranges = self.ranges_per_map.clamp(1, self.max_value as usize);
+            let mut cuts = sample(rng, self.max_value as usize - 1, ranges - 1)
+                .into_iter()
+                .map(|cut| cut as u64 + 1)
+                .collect_vec();
+            cuts.extend([0, self.max_value]);
+            cuts.sort_unstable();
+            let mut pieces = cuts.iter().tuple_windows().map(|(&start, &end)| start..end).collect_vec();
+            pieces.shuffle(rng);
+
+            lines.push(String::new());
+            lines.push(format!("{}-to-{} map:", category(map), category(map + 1)));
+            let mut destination = 0;
+            for piece in pieces {
+                lines.push(format!("{} {} {}", destination, piece.start, piece.end - piece.start));
+                destination += piece.end - piece.start;
+            }
+        }
+
+        join_lines(lines)
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.seed_ranges > 1 {
+            smaller.push(Day5 { seed_ranges: self.seed_ranges - 1, ..self.clone() });
+        }
+        if self.max_seed_range > 1 {
+            smaller.push(Day5 { max_seed_range: self.max_seed_range - 1, ..self.clone() });
+        }
+        if self.maps > 1 {
+            smaller.push(Day5 { maps: self.maps - 1, ..self.clone() });
+        }
+        if self.ranges_per_map > 1 {
+            smaller.push(Day5 { ranges_per_map: self.ranges_per_map - 1, ..self.clone() });
+        }
+        if self.max_value as usize > self.ranges_per_map.max(2) {
+            smaller.push(Day5 { max_value: self.max_value - 1, ..self.clone() });
+        }
+        smaller
+    }
+}
+
+/// Races, each can be won
+#[derive(Clone, Debug)]
+pub struct Day6 {
+    pub races: usize,
+    pub max_time: u64
+}
+
+impl Default for Day6 {
+    fn default() -> Self {
+        Day6 { races: 4, max_time: 100 }
+    }
+}
+
+impl InputGenerator for Day6 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        let (times, distances): (Vec<u64>, Vec<u64>) = (0..self.races)
+            .map(|_| {
+                let time = rng.gen_range(2..=self.max_time.max(2));
+                // holding for half of the time goes the farthest
+                (time, rng.gen_range(0..time * time / 4))
+            })
+            .unzip();
+
+        let format_list = |values: &[u64]| values.iter().map(|v| format!("{:>6}", v)).join("");
+        join_lines([
+            format!("Time:    {}", format_list(&times)),
+            format!("Distance:{}", format_list(&distances))
+        ])
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.races > 1 {
+            smaller.push(Day6 { races: self.races - 1, ..self.clone() });
+        }
+        if self.max_time > 2 {
+            smaller.push(Day6 { max_time: self.max_time / 2, ..self.clone() });
+            smaller.push(Day6 { max_time: self.max_time - 1, ..self.clone() });
+        }
+        smaller
+    }
+}
+
+/// Distinct Camel Cards hands with bids
+#[derive(Clone, Debug)]
+pub struct Day7 {
+    pub hands: usize,
+    pub max_bid: u32
+}
+
+impl Default for Day7 {
+    fn default() -> Self {
+        Day7 { hands: 1000, max_bid: 1000 }
+    }
+}
+
+impl InputGenerator for Day7 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        const CARDS: &[u8] = b"23456789TJQKA";
+        assert!(self.hands <= CARDS.len().pow(5), "Not enough distinct hands");
+
+        let mut hands = HashSet::new();
+        let mut lines = Vec::new();
+        while lines.len() < self.hands {
+            let hand: String = (0..5).map(|_| *CARDS.choose(rng).unwrap() as char).collect();
+            if hands.insert(hand.clone()) {
+                lines.push(format!("{} {}", hand, rng.gen_range(1..=self.max_bid)));
+            }
+        }
+        join_lines(lines)
+    }
+}
+
+/// Network where the walk from each `..A` node loops through a single `..Z` node.
+/// The loop lengths are multiples of the number of directions, from AAA the walk gets to ZZZ.
+#[derive(Clone, Debug)]
+pub struct Day8 {
+    pub directions: usize,
+    pub ghosts: usize,
+    // each loop goes through the directions up to this many times
+    pub max_rounds: usize
+}
+
+impl Default for Day8 {
+    fn default() -> Self {
+        Day8 { directions: 31, ghosts: 6, max_rounds: 30 }
+    }
+}
+
+impl InputGenerator for Day8 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        let directions: String = (0..self.directions).map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' }).collect();
+
+        // names of three letters, only the start and end nodes end with A and Z
+        fn new_name(rng: &mut StdRng, used: &mut HashSet<String>, last: impl Fn(&mut StdRng) -> u8) -> String {
+            loop {
+                let name: String = [rng.gen_range(b'A'..=b'Z'), rng.gen_range(b'A'..=b'Z'), last(rng)]
+                    .map(char::from)
+                    .iter()
+                    .collect();
+                if used.insert(name.clone()) {
+                    return name;
+                }
+            }
+        }
+        let mut used = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+
+        // (node, left, right)
+        let mut nodes: Vec<(String, String, String)> = Vec::new();
+        let mut loops: Vec<Vec<String>> = Vec::new();
+        for ghost in 0..self.ghosts {
+            let length = self.directions * rng.gen_range(1..=self.max_rounds.max(1));
+            assert!(used.len() + length < 26 * 26 * 24, "Too many nodes for names of three letters");
+
+            let (start, end) = if ghost == 0 {
+                ("AAA".to_string(), "ZZZ".to_string())
+            } else {
+                // the end shares the first two letters with the start, both have to be free
+                loop {
+                    let start = new_name(rng, &mut used, |_| b'A');
+                    let end = start[..2].to_string() + "Z";
+                    if used.insert(end.clone()) {
+                        break (start, end);
+                    }
+                }
+            };
+
+            let mut cycle = (0..length - 1).map(|_| new_name(rng, &mut used, |rng| rng.gen_range(b'B'..=b'Y'))).collect_vec();
+            cycle.push(end);
+            nodes.push((start, cycle[0].clone(), cycle[0].clone()));
+            loops.push(cycle);
+        }
+
+        let all_nodes = loops.iter().flatten().cloned().collect_vec();
+        for cycle in &loops {
+            for (idx, node) in cycle.iter().enumerate() {
+                // the node is reached after idx + 1 steps, and left in the same direction on every round
+                let next = cycle[(idx + 1) % cycle.len()].clone();
+                let other = all_nodes.choose(rng).unwrap().clone();
+                if directions.as_bytes()[(idx + 1) % self.directions] == b'L' {
+                    nodes.push((node.clone(), next, other));
+                } else {
+                    nodes.push((node.clone(), other, next));
+                }
+            }
+        }
+
+        nodes.shuffle(rng);
+        join_lines([directions, String::new()]
+            .into_iter()
+            .chain(nodes.into_iter().map(|(node, left, right)| format!("{} = ({}, {})", node, left, right))))
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.directions > 1 {
+            smaller.push(Day8 { directions: self.directions - 1, ..self.clone() });
+        }
+        if self.ghosts > 1 {
+            smaller.push(Day8 { ghosts: self.ghosts - 1, ..self.clone() });
+        }
+        if self.max_rounds > 1 {
+            smaller.push(Day8 { max_rounds: self.max_rounds - 1, ..self.clone() });
+        }
+        smaller
+    }
+}
+
+/// Polynomial sequences of small integers
+#[derive(Clone, Debug)]
+pub struct Day9 {
+    pub sequences: usize,
+    pub length: usize,
+    pub max_degree: usize,
+    pub max_coefficient: i64
+}
+
+impl Default for Day9 {
+    fn default() -> Self {
+        Day9 { sequences: 200, length: 21, max_degree: 6, max_coefficient: 10 }
+    }
+}
+
+impl InputGenerator for Day9 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        join_lines((0..self.sequences).map(|_| {
+            // at least two more values than the degree, to tell it is a polynomial
+            let degree = rng.gen_range(0..=self.max_degree.min(self.length.saturating_sub(2)));
+            let mut differences = (0..=degree)
+                .map(|_| rng.gen_range(-self.max_coefficient..=self.max_coefficient))
+                .collect_vec();
+
+            (0..self.length)
+                .map(|_| {
+                    let value = differences[0];
+                    for j in 0..degree {
+                        differences[j] += differences[j + 1];
+                    }
+                    value
+                })
+                .join(" ")
+        }))
+    }
+}
+
+/// Board with a single loop of pipes through S, and unconnected pipes inside and around it.
+/// The loop goes around a random tree, the more nodes it has the longer and more winding the loop.
+/// The loop has at most `width * height / 2` tiles.
+#[derive(Clone, Debug)]
+pub struct Day10 {
+    pub width: usize,
+    pub height: usize,
+    pub tree_nodes: usize,
+    // chance of a pipe, rather than ground, outside of the loop
+    pub junk: f64
+}
+
+impl Default for Day10 {
+    fn default() -> Self {
+        Day10 { width: 140, height: 140, tree_nodes: 1000, junk: 0.7 }
+    }
+}
+
+impl InputGenerator for Day10 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        assert!(self.width >= 3 && self.height >= 3, "The board is too small for a loop");
+
+        // random tree on a grid of nodes, the nodes and the edges between them make a shape
+        // on a twice as dense grid, without holes and without cells touching only by corners
+        let nodes = ((self.height + 1) / 4, (self.width + 1) / 4);
+        let mut shape = Array2::from_elem((2 * nodes.0 - 1, 2 * nodes.1 - 1), false);
+        let root = [rng.gen_range(0..nodes.0), rng.gen_range(0..nodes.1)];
+        shape[[2 * root[0], 2 * root[1]]] = true;
+
+        let mut frontier = vec![root];
+        let mut tree_nodes = 1;
+        while tree_nodes < self.tree_nodes && !frontier.is_empty() {
+            let idx = rng.gen_range(0..frontier.len());
+            let [y, x] = frontier[idx];
+            let unvisited = Connections::all()
+                .iter()
+                .map(|direction| direction.neighbour([y, x]))
+                .filter(|&[ny, nx]| ny < nodes.0 && nx < nodes.1 && !shape[[2 * ny, 2 * nx]])
+                .collect_vec();
+
+            let Some(&[ny, nx]) = unvisited.choose(rng) else {
+                frontier.swap_remove(idx);
+                continue;
+            };
+            shape[[2 * ny, 2 * nx]] = true;
+            shape[[y + ny, x + nx]] = true;
+            frontier.push([ny, nx]);
+            tree_nodes += 1;
+        }
+
+        // the loop goes along the border of the shape scaled twice, through the corners of its cells,
+        // so there are tiles inside the loop
+        let in_shape = |y: usize, x: usize| y > 0 && x > 0 && shape.get([(y - 1) / 2, (x - 1) / 2]).is_some_and(|&cell| cell);
+        let mut board = Array2::from_shape_fn((self.height, self.width), |(y, x)| {
+            let mut connections = Connections::empty();
+            if in_shape(y, x) != in_shape(y, x + 1) {
+                connections |= Connections::NORTH;
+            }
+            if in_shape(y + 1, x) != in_shape(y + 1, x + 1) {
+                connections |= Connections::SOUTH;
+            }
+            if in_shape(y, x) != in_shape(y + 1, x) {
+                connections |= Connections::WEST;
+            }
+            if in_shape(y, x + 1) != in_shape(y + 1, x + 1) {
+                connections |= Connections::EAST;
+            }
+            connections.to_tile().unwrap()
+        });
+
+        let loop_tiles = board.indexed_iter().filter(|(_, &tile)| tile != b'.').map(|(pos, _)| pos).collect_vec();
+        for tile in board.iter_mut().filter(|tile| **tile == b'.') {
+            if rng.gen_bool(self.junk) {
+                *tile = *b"|-LJ7F".choose(rng).unwrap();
+            }
+        }
+
+        // only the loop pipes may connect to S
+        let (y, x) = *loop_tiles.choose(rng).unwrap();
+        let start_connections = Connections::from_tile(board[[y, x]]).unwrap();
+        for direction in Connections::all().iter().filter(|&direction| !start_connections.contains(direction)) {
+            if let Some(tile) = board.get_mut(direction.neighbour([y, x])) {
+                if Connections::from_tile(*tile).unwrap().contains(direction.opposite()) {
+                    *tile = b'.';
+                }
+            }
+        }
+        board[[y, x]] = b'S';
+
+        join_lines(board.rows().into_iter().map(|row| row.iter().map(|&c| c as char).collect()))
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.width > 3 {
+            smaller.push(Day10 { width: self.width - 1, ..self.clone() });
+        }
+        if self.height > 3 {
+            smaller.push(Day10 { height: self.height - 1, ..self.clone() });
+        }
+        if self.tree_nodes > 1 {
+            smaller.push(Day10 { tree_nodes: self.tree_nodes - 1, ..self.clone() });
+        }
+        smaller
+    }
+}
+
+/// Image with galaxies placed at random
+#[derive(Clone, Debug)]
+pub struct Day11 {
+    pub width: usize,
+    pub height: usize,
+    pub density: f64
+}
+
+impl Default for Day11 {
+    fn default() -> Self {
+        Day11 { width: 140, height: 140, density: 0.02 }
+    }
+}
+
+impl InputGenerator for Day11 {
+    fn generate(&self, rng: &mut StdRng) -> String {
+        join_lines((0..self.height).map(|_| {
+            (0..self.width).map(|_| if rng.gen_bool(self.density) { '#' } else { '.' }).collect()
+        }))
+    }
+
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.width > 1 {
+            smaller.push(Day11 { width: self.width - 1, ..self.clone() });
+        }
+        if self.height > 1 {
+            smaller.push(Day11 { height: self.height - 1, ..self.clone() });
+        }
+        smaller
+    }
+}
+
+#[test]
+fn generate_reproducible() {
+    let generators: [Box<dyn InputGenerator>; 11] = [
+        Box::new(Day1::default()), Box::new(Day2::default()), Box::new(Day3::default()),
+        Box::new(Day4::default()), Box::new(Day5::default()), Box::new(Day6::default()),
+        Box::new(Day7::default()), Box::new(Day8 { directions: 7, ghosts: 3, max_rounds: 5 }),
+        Box::new(Day9::default()), Box::new(Day10 { width: 30, height: 20, tree_nodes: 50, junk: 0.5 }),
+        Box::new(Day11::default()),
+    ];
+    for generator in generators {
+        let input = generator.generate_seeded(1);
+        assert!(input.ends_with('\n'));
+        assert!(input == generator.generate_seeded(1));
+        assert!(input != generator.generate_seeded(2));
+    }
+}
+
+#[test]
+fn generate_day1_line_lengths() {
+    let input = Day1 { lines: 2000, line_length: 40, word_chance: 0.1, digit_chance: 0.05 }.generate_seeded(1);
+    let lengths = input.lines().map(str::len).collect_vec();
+    // lengths spread evenly up to the limit, a few more for the words and the digit
+    let average = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+    assert!((20.0..26.0).contains(&average), "Average length {}", average);
+    assert!(lengths.iter().filter(|&&length| length > 30).count() > 400);
+}
+
+#[test]
+fn generate_day4_cards() {
+    let input = Day4 { cards: 20, winning: 5, held: 8, max_number: 20, match_chance: 0.5 }.generate_seeded(4);
+    let lines = input.lines().collect_vec();
+    assert!(lines.len() == 20);
+
+    for (idx, line) in lines.iter().enumerate() {
+        let (label, numbers) = line.split_once(": ").unwrap();
+        assert!(label.trim_start_matches("Card ").trim().parse::<usize>().unwrap() == idx + 1);
+
+        let (winning, held) = numbers.split_once(" | ").unwrap();
+        let parse = |s: &str| s.split_whitespace().map(|n| n.parse::<usize>().unwrap()).collect::<HashSet<_>>();
+        let (winning, held) = (parse(winning), parse(held));
+        assert!(winning.len() == 5 && held.len() == 8);
+        assert!(winning.intersection(&held).count() < lines.len() - idx);
+    }
+}
+
+#[test]
+fn generate_day5_maps_cover_all_values() {
+    let input = Day5 { seed_ranges: 3, max_seed_range: 10, maps: 2, ranges_per_map: 5, max_value: 100 }.generate_seeded(5);
+    let sections = input.split("\n\n").collect_vec();
+    assert!(sections.len() == 3);
+    assert!(sections[0].split(' ').count() == 1 + 3 * 2);
+    assert!(sections[1].starts_with("seed-to-soil map:\n"));
+
+    for section in &sections[1..] {
+        let ranges = section
+            .lines()
+            .skip(1)
+            .map(|line| line.split(' ').map(|n| n.parse::<u64>().unwrap()).collect_vec())
+            .collect_vec();
+        assert!(ranges.len() == 5);
+        for side in [0, 1] {
+            let covered = ranges.iter().map(|range| range[side]..range[side] + range[2]).sorted_by_key(|r| r.start);
+            assert!(covered.fold(0, |end, range| { assert!(range.start == end); range.end }) == 100);
+        }
+    }
+}
+
+#[test]
+fn generate_day8_unique_nodes() {
+    let generator = Day8 { directions: 3, ghosts: 6, max_rounds: 3 };
+    for seed in 0..200 {
+        let input = generator.generate_seeded(seed);
+        let nodes = input.lines().skip(2).map(|line| &line[..3]).collect_vec();
+        assert!(nodes.iter().all_unique(), "Seed {} repeats a node", seed);
+        assert!(nodes.iter().filter(|node| node.ends_with('A')).count() == 6);
+        assert!(nodes.iter().filter(|node| node.ends_with('Z')).count() == 6);
+    }
+}
+
+#[test]
+fn generate_day9_polynomials() {
+    use crate::sequence::PolynomialSequence;
+
+    let input = Day9 { sequences: 20, length: 8, max_degree: 5, max_coefficient: 3 }.generate_seeded(9);
+    for line in input.lines() {
+        let values = line.split(' ').map(|n| n.parse::<i64>().unwrap()).collect_vec();
+        assert!(values.len() == 8);
+        assert!(PolynomialSequence::fit(&values).unwrap().degree() <= 5);
+    }
+}
+
+#[test]
+fn generate_day10_single_loop() {
+    let input = Day10 { width: 41, height: 30, tree_nodes: 100, junk: 0.8 }.generate_seeded(10);
+    let lines = input.lines().collect_vec();
+    assert!(lines.len() == 30 && lines.iter().all(|line| line.len() == 41));
+    let board = crate::InputBuffer::new(input.clone()).to_2d_board();
+
+    let (start, _) = board.indexed_iter().find(|(_, &tile)| tile == b'S').unwrap();
+    let start = [start.0, start.1];
+    assert!(crate::pipes::start_directions(board.view(), start).len() == 2);
+
+    // the loop leads back to S
+    let length = crate::pipes::loop_from_start(board.view(), start).unwrap().len();
+    assert!(length >= 4 && length.is_multiple_of(2));
+}
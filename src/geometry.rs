@@ -0,0 +1,147 @@
+use itertools::Itertools;
+use num_integer::Integer;
+
+/// Closed polygon with vertices on integer coordinates, the last vertex connects to the first one.
+/// Vertices may be listed in any direction, collinear vertices are allowed.
+pub struct LatticePolygon {
+    vertices: Vec<[i64; 2]>
+}
+
+impl LatticePolygon {
+    pub fn new(vertices: Vec<[i64; 2]>) -> Self {
+        LatticePolygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[[i64; 2]] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&[i64; 2], &[i64; 2])> {
+        self.vertices.iter().circular_tuple_windows()
+    }
+
+    /// Twice the area, always an integer for lattice polygons.
+    /// https://en.wikipedia.org/wiki/Shoelace_formula
+    pub fn double_area(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| a[0] * b[1] - a[1] * b[0])
+            .sum::<i64>()
+            .abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.double_area() as f64 / 2.
+    }
+
+    /// Number of lattice points lying on the edges
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| (b[0] - a[0]).gcd(&(b[1] - a[1])))
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside the polygon.
+    /// https://en.wikipedia.org/wiki/Pick%27s_theorem: A = I + B/2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points() + 2) / 2
+    }
+}
+
+/// Sum of |a - b| over all pairs of values, a value with weight `w` stands for `w` equal values.
+/// Sorts the values, O(N log N).
+fn pairwise_distance_sum_1d(values: &mut [(i64, u64)]) -> u128 {
+    values.sort_unstable();
+
+    let mut acc: i128 = 0;
+    let mut count_prev: i128 = 0;
+    let mut sum_prev: i128 = 0;
+
+    for &(value, weight) in values.iter() {
+        let (value, weight) = (value as i128, weight as i128);
+        // distances from the current value to all the previous (smaller) ones
+        acc += weight * (value * count_prev - sum_prev);
+        count_prev += weight;
+        sum_prev += weight * value;
+    }
+
+    acc as u128
+}
+
+/// Sum of Manhattan distances between all pairs of points in D dimensions, in O(N log N)
+pub fn pairwise_manhattan_sum<const D: usize>(points: &[[i64; D]]) -> u128 {
+    let weighted = points.iter().map(|&point| (point, 1)).collect_vec();
+    weighted_pairwise_manhattan_sum(&weighted, |_, coordinate| coordinate)
+}
+
+/// Sum of Manhattan distances between all pairs of points in D dimensions, in O(N log N).
+/// A point with weight `w` stands for `w` points in the same place.
+/// Coordinates are remapped with `remap(axis, coordinate)` first, e.g. to stretch some lines.
+pub fn weighted_pairwise_manhattan_sum<const D: usize>(
+        points: &[([i64; D], u64)],
+        remap: impl Fn(usize, i64) -> i64) -> u128 {
+    (0..D)
+        .map(|axis| {
+            let mut values = points
+                .iter()
+                .map(|(point, weight)| (remap(axis, point[axis]), *weight))
+                .collect_vec();
+            pairwise_distance_sum_1d(&mut values)
+        })
+        .sum()
+}
+
+#[test]
+fn geometry_square() {
+    let square = LatticePolygon::new(vec![[0, 0], [0, 4], [4, 4], [4, 0]]);
+    assert!(square.double_area() == 32);
+    assert!(square.area() == 16.);
+    assert!(square.boundary_points() == 16);
+    assert!(square.interior_points() == 9);
+}
+
+#[test]
+fn geometry_lattice_polygons() {
+    let triangle = LatticePolygon::new(vec![[0, 0], [6, 0], [0, 3]]);
+    assert!(triangle.double_area() == 18);
+    assert!(triangle.boundary_points() == 12);
+    assert!(triangle.interior_points() == 4);
+
+    // the same L shape, counterclockwise with every lattice point, and clockwise corners only
+    let l_shape_points = vec![[0, 0], [1, 0], [2, 0], [2, 1], [1, 1], [1, 2], [0, 2], [0, 1]];
+    let l_shape_corners = vec![[0, 2], [1, 2], [1, 1], [2, 1], [2, 0], [0, 0]];
+    for l_shape in [l_shape_points, l_shape_corners] {
+        let l_shape = LatticePolygon::new(l_shape);
+        assert!(l_shape.double_area() == 6);
+        assert!(l_shape.boundary_points() == 8);
+        assert!(l_shape.interior_points() == 0);
+    }
+}
+
+#[test]
+fn geometry_pairwise_manhattan_sum() {
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+    let points: Vec<[i64; 4]> = (0..50)
+        .map(|_| [(); 4].map(|_| rng.gen_range(-100..100)))
+        .collect();
+
+    let brute_force: u64 = points
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| (0..4).map(|axis| a[axis].abs_diff(b[axis])).sum::<u64>())
+        .sum();
+    assert!(pairwise_manhattan_sum(&points) == brute_force as u128);
+    assert!(pairwise_manhattan_sum::<3>(&[]) == 0);
+    assert!(pairwise_manhattan_sum(&[[1, 2, 3]]) == 0);
+}
+
+#[test]
+fn geometry_weighted_pairwise_manhattan_sum() {
+    // two points at [0, 0, 0], three at [1, 2, 3]: 2 * 3 pairs of distance 6
+    let points = [([0, 0, 0], 2), ([1, 2, 3], 3)];
+    assert!(weighted_pairwise_manhattan_sum(&points, |_, c| c) == 36);
+
+    // x stretched 10 times: distance 15
+    assert!(weighted_pairwise_manhattan_sum(&points, |axis, c| if axis == 0 { 10 * c } else { c }) == 90);
+}
@@ -0,0 +1,158 @@
+use std::ops::Range;
+
+use ndarray::{Array2, ArrayView2};
+use thiserror::Error;
+
+/// Consecutive digits in a single row of a board
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DigitRun {
+    pub row: usize,
+    // columns of the digits, end exclusive
+    pub columns: Range<usize>,
+    pub value: u64
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Number in row {row}, columns {}..{}, does not fit in 64 bits", columns.start, columns.end)]
+pub struct DigitRunOverflow {
+    pub row: usize,
+    pub columns: Range<usize>
+}
+
+/// All runs of digits on the board, row by row.
+/// Runs never continue to the next row, leading zeros are part of the run.
+/// Fails on the first run with a value past `u64::MAX`.
+pub fn digit_runs(board: ArrayView2<u8>) -> Result<Vec<DigitRun>, DigitRunOverflow> {
+    let mut runs = Vec::new();
+
+    for (y, row) in board.rows().into_iter().enumerate() {
+        let mut current: Option<DigitRun> = None;
+
+        for (x, c) in row.iter().enumerate() {
+            match (c.is_ascii_digit(), current.as_mut()) {
+                (true, Some(run)) => {
+                    run.columns.end = x + 1;
+                    run.value = run.value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add((c - b'0') as u64))
+                        .ok_or_else(|| {
+                            let end = x + row.iter().skip(x).take_while(|c| c.is_ascii_digit()).count();
+                            DigitRunOverflow { row: y, columns: run.columns.start..end }
+                        })?;
+                },
+                (true, None) => {
+                    current = Some(DigitRun { row: y, columns: x..(x + 1), value: (c - b'0') as u64 });
+                },
+                (false, _) => runs.extend(current.take())
+            }
+        }
+
+        runs.extend(current);
+    }
+
+    Ok(runs)
+}
+
+/// Which cells count as touching
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Adjacency {
+    // sharing a side
+    Four,
+    // sharing a side or a corner
+    Eight
+}
+
+impl Adjacency {
+    /// [dy, dx] offsets of the neighbours
+    pub fn offsets(self) -> &'static [[isize; 2]] {
+        match self {
+            Adjacency::Four => &[[-1, 0], [0, -1], [0, 1], [1, 0]],
+            Adjacency::Eight => &[[-1, -1], [-1, 0], [-1, 1], [0, -1], [0, 1], [1, -1], [1, 0], [1, 1]]
+        }
+    }
+}
+
+/// Maps cells of a board to IDs of objects covering them, e.g. numbers spanning a few cells.
+/// Each cell holds at most one object.
+pub struct GridIndex {
+    ids: Array2<u32>
+}
+
+impl GridIndex {
+    const EMPTY: u32 = u32::MAX;
+
+    pub fn new(dim: (usize, usize)) -> Self {
+        GridIndex { ids: Array2::from_elem(dim, Self::EMPTY) }
+    }
+
+    pub fn insert(&mut self, id: usize, cells: impl IntoIterator<Item = [usize; 2]>) {
+        let id = u32::try_from(id).ok().filter(|&id| id != Self::EMPTY).expect("Too many objects in the index");
+        for cell in cells {
+            self.ids[cell] = id;
+        }
+    }
+
+    pub fn get(&self, cell: [usize; 2]) -> Option<usize> {
+        self.ids
+            .get(cell)
+            .filter(|&&id| id != Self::EMPTY)
+            .map(|&id| id as usize)
+    }
+
+    /// IDs of the objects in the cells adjacent to the cell, each listed once
+    pub fn around(&self, [y, x]: [usize; 2], adjacency: Adjacency) -> Vec<usize> {
+        let mut result = Vec::new();
+        for &[dy, dx] in adjacency.offsets() {
+            let neighbour = [y.wrapping_add_signed(dy), x.wrapping_add_signed(dx)];
+            if let Some(id) = self.get(neighbour) {
+                if !result.contains(&id) {
+                    result.push(id);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[test]
+fn grid_digit_runs() {
+    let lines = ["007*0..", "..12345", "6.....8"];
+    let board = crate::InputBuffer::new(lines.join("\n")).to_2d_board();
+    let runs = digit_runs(board.view()).unwrap();
+
+    let expected = [
+        DigitRun { row: 0, columns: 0..3, value: 7 },
+        DigitRun { row: 0, columns: 4..5, value: 0 },
+        DigitRun { row: 1, columns: 2..7, value: 12345 },
+        DigitRun { row: 2, columns: 0..1, value: 6 },
+        DigitRun { row: 2, columns: 6..7, value: 8 },
+    ];
+    assert!(runs == expected);
+}
+
+#[test]
+fn grid_digit_run_overflow() {
+    // u64::MAX has 20 digits
+    let lines = ["18446744073709551615.", "018446744073709551616"];
+    let board = crate::InputBuffer::new(lines.join("\n")).to_2d_board();
+    assert!(digit_runs(board.view()) == Err(DigitRunOverflow { row: 1, columns: 0..21 }));
+
+    let board = crate::InputBuffer::new(lines[0].to_string()).to_2d_board();
+    assert!(digit_runs(board.view()).unwrap()[0].value == u64::MAX);
+}
+
+#[test]
+fn grid_index() {
+    let mut index = GridIndex::new((3, 4));
+    index.insert(0, [[0, 0], [0, 1], [0, 2]]);
+    index.insert(1, [[2, 3]]);
+
+    assert!(index.get([0, 1]) == Some(0));
+    assert!(index.get([1, 1]).is_none());
+    assert!(index.get([5, 5]).is_none());
+    assert!(index.around([1, 2], Adjacency::Eight) == [0, 1]);
+    assert!(index.around([1, 2], Adjacency::Four) == [0]);
+    assert!(index.around([1, 3], Adjacency::Four) == [1]);
+    assert!(index.around([0, 0], Adjacency::Eight) == [0]);
+    assert!(index.around([2, 0], Adjacency::Eight).is_empty());
+}
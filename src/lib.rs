@@ -1,5 +1,16 @@
 #![allow(clippy::reversed_empty_ranges)]
 
+pub mod aho_corasick;
+#[cfg(any(test, feature = "differential"))]
+pub mod differential;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod number_set;
+pub mod pipes;
+pub mod progress;
+pub mod sequence;
+
 use std::fs;
 use itertools::Itertools;
 use ndarray::{Array2, ArrayView2, azip, s};
@@ -12,13 +23,53 @@ pub fn read_input_lines(input_path: &str) -> Vec<String> {
         .collect()
 }
 
+/// Content of the whole input file in a single buffer, lines are borrowed from it
+pub struct InputBuffer {
+    data: String
+}
+
+impl InputBuffer {
+    pub fn new(data: String) -> Self {
+        InputBuffer { data }
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.data.lines()
+    }
+
+    /// Lines as bytes, without line endings, for grid-like inputs
+    pub fn byte_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines().map(str::as_bytes)
+    }
+
+    /// 2D board of characters, copied once into the board's storage
+    pub fn to_2d_board(&self) -> Array2<u8> {
+        let width = self.byte_lines().next().map_or(0, <[u8]>::len);
+        let mut input = Vec::with_capacity(self.data.len());
+        let mut height = 0;
+        for line in self.byte_lines() {
+            assert!(line.len() == width, "All lines of the board should have the same length");
+            input.extend_from_slice(line);
+            height += 1;
+        }
+
+        Array2::from_shape_vec((height, width), input).unwrap()
+    }
+}
+
+pub fn read_input(input_path: &str) -> InputBuffer {
+    let data = fs::read_to_string(input_path)
+        .unwrap_or_else(|err| panic!("Could not open the file {}, {}", input_path, err));
+    InputBuffer::new(data)
+}
+
 pub fn read_2d_board(input_path: &str) -> Array2<u8> {
-    let lines = read_input_lines(input_path);
-    let width = lines[0].len();
-    let height = lines.len();
-    let input = lines.join("").into_bytes();
+    read_input(input_path).to_2d_board()
+}
 
-    Array2::from_shape_vec((height, width), input).unwrap()
+/// Value following a flag in the command line arguments, e.g. `7` for `--seed 7`
+pub fn flag_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().skip_while(|arg| *arg != name).nth(1).map(String::as_str)
 }
 
 pub fn print_ascii_board(board: ArrayView2<u8>) {
@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+use itertools::Either;
+
+/// Set of non-negative numbers. Numbers below 128 are kept as bits of a `u128`,
+/// so intersecting sets of small numbers is a single `and` and popcount.
+/// Larger numbers go to a bitset of words when they are dense enough, to a sorted list otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct NumberSet {
+    small: u128,
+    large: Large
+}
+
+/// Numbers from 128 on
+#[derive(Clone, Debug)]
+enum Large {
+    // bit `n - 128` stands for `n`
+    Bits(Vec<u64>),
+    Sorted(Vec<usize>)
+}
+
+impl Default for Large {
+    fn default() -> Self {
+        Large::Sorted(Vec::new())
+    }
+}
+
+impl NumberSet {
+    const SMALL_LIMIT: usize = u128::BITS as usize;
+    // most words a bitset may take per number in it, sparser numbers are kept sorted
+    const WORDS_PER_NUMBER: usize = 8;
+    // most pairs `common_count` compares one by one instead of building sets
+    const PAIRWISE_LIMIT: usize = 10_000;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Word and bit of a large number in a bitset
+    fn bit_position(n: usize) -> (usize, u64) {
+        let idx = n - Self::SMALL_LIMIT;
+        (idx / 64, 1 << (idx % 64))
+    }
+
+    /// Adds the number, returns false if it was already in the set
+    pub fn insert(&mut self, n: usize) -> bool {
+        if n < Self::SMALL_LIMIT {
+            let bit = 1u128 << n;
+            let is_new = self.small & bit == 0;
+            self.small |= bit;
+            return is_new;
+        }
+
+        let large_count = self.len() - self.small.count_ones() as usize;
+        if let Large::Bits(words) = &mut self.large {
+            let (word, bit) = Self::bit_position(n);
+            if word < words.len() || word < Self::WORDS_PER_NUMBER * (large_count + 1) {
+                if word >= words.len() {
+                    words.resize(word + 1, 0);
+                }
+                let is_new = words[word] & bit == 0;
+                words[word] |= bit;
+                return is_new;
+            }
+            // too far for the bitset
+            self.large = Large::Sorted(self.iter().filter(|&n| n >= Self::SMALL_LIMIT).collect());
+        }
+
+        let Large::Sorted(numbers) = &mut self.large else { unreachable!() };
+        match numbers.binary_search(&n) {
+            Ok(_) => false,
+            Err(idx) => {
+                numbers.insert(idx, n);
+                true
+            }
+        }
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        if n < Self::SMALL_LIMIT {
+            return self.small & (1u128 << n) != 0;
+        }
+        match &self.large {
+            Large::Bits(words) => {
+                let (word, bit) = Self::bit_position(n);
+                words.get(word).is_some_and(|&w| w & bit != 0)
+            }
+            Large::Sorted(numbers) => numbers.binary_search(&n).is_ok()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let large = match &self.large {
+            Large::Bits(words) => words.iter().map(|w| w.count_ones() as usize).sum(),
+            Large::Sorted(numbers) => numbers.len()
+        };
+        self.small.count_ones() as usize + large
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements in both sets
+    pub fn intersection_count(&self, other: &NumberSet) -> usize {
+        let small = (self.small & other.small).count_ones() as usize;
+        let large = match (&self.large, &other.large) {
+            (Large::Bits(a), Large::Bits(b)) => a.iter().zip(b).map(|(a, b)| (a & b).count_ones() as usize).sum(),
+            (Large::Bits(_), Large::Sorted(numbers)) => numbers.iter().filter(|&&n| self.contains(n)).count(),
+            (Large::Sorted(numbers), Large::Bits(_)) => numbers.iter().filter(|&&n| other.contains(n)).count(),
+            (Large::Sorted(a), Large::Sorted(b)) => {
+                // merge of the sorted lists
+                let (mut i, mut j, mut common) = (0, 0, 0);
+                while i < a.len() && j < b.len() {
+                    match a[i].cmp(&b[j]) {
+                        Ordering::Less => i += 1,
+                        Ordering::Greater => j += 1,
+                        Ordering::Equal => {
+                            common += 1;
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                }
+                common
+            }
+        };
+        small + large
+    }
+
+    /// Number of distinct values in both lists. Small numbers go to the `u128`s,
+    /// short lists with large numbers are compared pair by pair, as building sets costs more
+    pub fn common_count(a: &[usize], b: &[usize]) -> usize {
+        if a.iter().chain(b).all(|&n| n < Self::SMALL_LIMIT) {
+            let bits = |list: &[usize]| list.iter().fold(0u128, |bits, &n| bits | 1 << n);
+            return (bits(a) & bits(b)).count_ones() as usize;
+        }
+        if a.len() * b.len() <= Self::PAIRWISE_LIMIT {
+            return a
+                .iter()
+                .enumerate()
+                .filter(|&(idx, n)| b.contains(n) && !a[..idx].contains(n))
+                .count();
+        }
+        let a: NumberSet = a.iter().cloned().collect();
+        let b: NumberSet = b.iter().cloned().collect();
+        a.intersection_count(&b)
+    }
+
+    /// Elements in the increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let small = (0..Self::SMALL_LIMIT).filter(|&n| self.small & (1u128 << n) != 0);
+        let large = match &self.large {
+            Large::Bits(words) => Either::Left(words.iter().enumerate().flat_map(|(word, &bits)| {
+                (0..64)
+                    .filter(move |bit| bits & (1 << bit) != 0)
+                    .map(move |bit| Self::SMALL_LIMIT + word * 64 + bit)
+            })),
+            Large::Sorted(numbers) => Either::Right(numbers.iter().cloned())
+        };
+        small.chain(large)
+    }
+}
+
+impl PartialEq for NumberSet {
+    /// Same numbers, however they are kept
+    fn eq(&self, other: &Self) -> bool {
+        self.small == other.small && self.iter().eq(other.iter())
+    }
+}
+
+impl FromIterator<usize> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = NumberSet::new();
+        let mut large = Vec::new();
+        for n in iter {
+            if n < Self::SMALL_LIMIT {
+                set.small |= 1u128 << n;
+            } else {
+                large.push(n);
+            }
+        }
+
+        let words = large.iter().max().map_or(0, |&max| Self::bit_position(max).0 + 1);
+        if words > 0 && words <= Self::WORDS_PER_NUMBER * large.len() {
+            let mut bits = vec![0u64; words];
+            for n in large {
+                let (word, bit) = Self::bit_position(n);
+                bits[word] |= bit;
+            }
+            set.large = Large::Bits(bits);
+        } else {
+            large.sort_unstable();
+            large.dedup();
+            set.large = Large::Sorted(large);
+        }
+        set
+    }
+}
+
+#[test]
+fn number_set_small_and_large() {
+    let mut set = NumberSet::new();
+    assert!(set.is_empty());
+    assert!(set.insert(5));
+    assert!(set.insert(127));
+    assert!(set.insert(128));
+    assert!(set.insert(1000));
+    assert!(!set.insert(5));
+    assert!(!set.insert(1000));
+
+    assert!(set.len() == 4);
+    assert!(set.contains(127) && set.contains(128) && !set.contains(6) && !set.contains(999));
+    assert!(set.iter().collect::<Vec<_>>() == [5, 127, 128, 1000]);
+}
+
+#[test]
+fn number_set_intersection() {
+    let winning: NumberSet = [41, 48, 83, 86, 17].into_iter().collect();
+    let held: NumberSet = [83, 86, 6, 31, 17, 9, 48, 53].into_iter().collect();
+    assert!(winning.intersection_count(&held) == 4);
+
+    let a: NumberSet = [0, 1, 200, 300, 4000].into_iter().collect();
+    let b: NumberSet = [1, 2, 300, 4000, 5000, 6000].into_iter().collect();
+    assert!(a.intersection_count(&b) == 3);
+    assert!(b.intersection_count(&a) == 3);
+    assert!(a.intersection_count(&NumberSet::new()) == 0);
+}
+
+#[test]
+fn number_set_dense_and_sparse() {
+    // dense numbers go to a bitset, sparse ones stay sorted
+    let dense: NumberSet = (100..1100).step_by(3).chain([500, 130]).collect();
+    let sparse: NumberSet = [129, 700, 1_000_000, 5_000_000_000, 700].into_iter().collect();
+    assert!(matches!(dense.large, Large::Bits(_)));
+    assert!(matches!(sparse.large, Large::Sorted(_)));
+
+    assert!(dense.len() == 335);
+    assert!(sparse.len() == 4);
+    assert!(dense.contains(1099) && !dense.contains(1100) && !dense.contains(1_000_000));
+    assert!(dense.intersection_count(&sparse) == 1);
+    assert!(sparse.intersection_count(&dense) == 1);
+    assert!(dense.intersection_count(&dense) == 335);
+
+    // growing past the bitset turns it into a list
+    let mut set: NumberSet = (128..200).collect();
+    assert!(set.insert(300));
+    assert!(matches!(set.large, Large::Bits(_)));
+    assert!(set.insert(1 << 40));
+    assert!(matches!(set.large, Large::Sorted(_)));
+    assert!(!set.insert(300));
+    assert!(set == (128..200).chain([300, 1 << 40]).collect());
+}
+
+#[test]
+fn number_set_common_count() {
+    // duplicates count once, with small, pairwise and set counting
+    assert!(NumberSet::common_count(&[41, 48, 83, 48, 17], &[83, 48, 6, 17, 9]) == 3);
+    assert!(NumberSet::common_count(&[4100, 48, 830, 48, 17], &[830, 48, 6, 17, 9, 48]) == 3);
+    let a: Vec<usize> = (0..300).map(|n| n * 7).chain([14, 1_000_000]).collect();
+    let b: Vec<usize> = (0..300).map(|n| n * 5).chain([1_000_000]).collect();
+    assert!(a.len() * b.len() > NumberSet::PAIRWISE_LIMIT);
+    assert!(NumberSet::common_count(&a, &b) == (0..300 * 5).filter(|n| n % 35 == 0).count() + 1);
+}
@@ -0,0 +1,258 @@
+use bitflags::bitflags;
+use itertools::Itertools;
+use ndarray::{Array2, ArrayView2};
+use std::collections::VecDeque;
+
+bitflags! {
+    /// Sides of a tile that connect to its neighbours
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Connections: u8 {
+        const NORTH = 0b0001;
+        const EAST  = 0b0010;
+        const SOUTH = 0b0100;
+        const WEST  = 0b1000;
+    }
+}
+
+impl Connections {
+    /// Connections of pipe tiles: `|-LJ7F`, ground `.` has none. None for other characters.
+    pub fn from_tile(tile: u8) -> Option<Self> {
+        match tile {
+            b'|' => Some(Self::NORTH | Self::SOUTH),
+            b'-' => Some(Self::EAST | Self::WEST),
+            b'L' => Some(Self::NORTH | Self::EAST),
+            b'J' => Some(Self::NORTH | Self::WEST),
+            b'7' => Some(Self::SOUTH | Self::WEST),
+            b'F' => Some(Self::SOUTH | Self::EAST),
+            b'.' => Some(Self::empty()),
+            _ => None
+        }
+    }
+
+    /// Inverse of `from_tile`, None if no tile has these connections
+    pub fn to_tile(self) -> Option<u8> {
+        b"|-LJ7F.".iter().cloned().find(|&tile| Self::from_tile(tile) == Some(self))
+    }
+
+    /// Box-drawing character, e.g. `└` for north and east
+    pub fn to_box_drawing(self) -> char {
+        const BOX_DRAWING: [char; 16] = [
+            ' ', '╵', '╶', '└', '╷', '│', '┌', '├',
+            '╴', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+        ];
+        BOX_DRAWING[self.bits() as usize]
+    }
+
+    /// Sides swapped: north with south, east with west
+    pub fn opposite(self) -> Self {
+        Self::from_bits_truncate((self.bits() << 2 | self.bits() >> 2) & Self::all().bits())
+    }
+
+    /// Position of the neighbour in a single direction, `[y, x]` with y growing to the south.
+    /// Wraps around outside of the board, so the result is a non-existing position.
+    pub fn neighbour(self, [y, x]: [usize; 2]) -> [usize; 2] {
+        match self {
+            Self::NORTH => [y.wrapping_sub(1), x],
+            Self::EAST  => [y, x.wrapping_add(1)],
+            Self::SOUTH => [y.wrapping_add(1), x],
+            Self::WEST  => [y, x.wrapping_sub(1)],
+            _ => panic!("Neighbour is defined only for a single direction")
+        }
+    }
+
+    /// Single direction from a position to its adjacent neighbour
+    pub fn direction_to(from: [usize; 2], to: [usize; 2]) -> Option<Self> {
+        Self::all().iter().find(|direction| direction.neighbour(from) == to)
+    }
+
+    /// Does the tile connect to the neighbour in the direction, and the neighbour connect back
+    pub fn connects_with(self, neighbour: Self, direction: Self) -> bool {
+        self.contains(direction) && neighbour.contains(direction.opposite())
+    }
+
+    /// Do both the tile and the neighbour in the direction agree if they connect or not
+    pub fn compatible_with(self, neighbour: Self, direction: Self) -> bool {
+        self.contains(direction) == neighbour.contains(direction.opposite())
+    }
+}
+
+/// Directions from the start to the neighbours with pipes connecting back to it
+pub fn start_directions(board: ArrayView2<u8>, start: [usize; 2]) -> Vec<Connections> {
+    Connections::all()
+        .iter()
+        .filter(|&direction| board
+            .get(direction.neighbour(start))
+            .and_then(|&tile| Connections::from_tile(tile))
+            .is_some_and(|connections| connections.contains(direction.opposite())))
+        .collect()
+}
+
+/// Positions of the loop through the start, in order and beginning with the start.
+/// The loop leaves in the first of `start_directions`, None if the pipes break off before coming back.
+pub fn loop_from_start(board: ArrayView2<u8>, start: [usize; 2]) -> Option<Vec<[usize; 2]>> {
+    let first = *start_directions(board, start).first()?;
+
+    let mut positions = vec![start];
+    let (mut position, mut came_from) = (first.neighbour(start), first.opposite());
+    while position != start {
+        positions.push(position);
+        let connections = Connections::from_tile(*board.get(position)?)?;
+        let direction = connections.difference(came_from);
+        if !connections.contains(came_from) || direction.bits().count_ones() != 1 {
+            return None;
+        }
+        (position, came_from) = (direction.neighbour(position), direction.opposite());
+    }
+    Some(positions)
+}
+
+/// Prints pipes with box-drawing characters, other characters as they are
+pub fn print_pipe_board(board: ArrayView2<u8>) {
+    let board_string = board
+        .rows()
+        .into_iter()
+        .map(|row| row
+            .iter()
+            .map(|&c| Connections::from_tile(c).map_or(c as char, Connections::to_box_drawing))
+            .join(""))
+        .join("\n");
+    println!("{}", board_string);
+}
+
+/// Tiles enclosed by pipes, every pipe on the board is treated as a wall.
+/// The board is upscaled `scale` times (at least 2), so that the gaps between parallel pipes
+/// become passable, then flood-filled from the outside. Pipe tiles are never enclosed.
+pub fn enclosed_tiles(board: ArrayView2<u8>, scale: usize) -> Array2<bool> {
+    assert!(scale >= 2, "Without upscaling there are no gaps between pipes");
+
+    let (height, width) = board.dim();
+    let center = (scale - 1) / 2;
+    let to_upscaled = |[y, x]: [usize; 2]| [y * scale + center, x * scale + center];
+
+    // walls of the upscaled board: tile's center and its arms towards the connected sides
+    let mut is_wall = Array2::from_elem((height * scale, width * scale), false);
+    for ((y, x), &tile) in board.indexed_iter() {
+        let connections = Connections::from_tile(tile).unwrap_or(Connections::empty());
+        if connections.is_empty() { continue; }
+
+        let tile_center = to_upscaled([y, x]);
+        is_wall[tile_center] = true;
+        for direction in connections.iter() {
+            let arm_length = match direction {
+                Connections::NORTH | Connections::WEST => center,
+                _ => scale - 1 - center
+            };
+            let mut position = tile_center;
+            for _ in 0..arm_length {
+                position = direction.neighbour(position);
+                is_wall[position] = true;
+            }
+        }
+    }
+
+    // flood fill from all the cells on the edge
+    let (upscaled_height, upscaled_width) = is_wall.dim();
+    let mut is_outside = Array2::from_elem(is_wall.raw_dim(), false);
+    let mut queue: VecDeque<[usize; 2]> = is_wall
+        .indexed_iter()
+        .filter(|&((y, x), _)| y == 0 || x == 0 || y == upscaled_height - 1 || x == upscaled_width - 1)
+        .map(|((y, x), _)| [y, x])
+        .collect();
+
+    while let Some(position) = queue.pop_front() {
+        if is_wall[position] || is_outside[position] { continue; }
+        is_outside[position] = true;
+        queue.extend(Connections::all()
+            .iter()
+            .map(|direction| direction.neighbour(position))
+            .filter(|&neighbour| is_wall.get(neighbour).is_some()));
+    }
+
+    Array2::from_shape_fn(board.raw_dim(), |(y, x)| {
+        let tile_center = to_upscaled([y, x]);
+        !is_wall[tile_center] && !is_outside[tile_center]
+    })
+}
+
+#[test]
+fn pipes_tiles() {
+    for tile in *b"|-LJ7F." {
+        assert!(Connections::from_tile(tile).unwrap().to_tile() == Some(tile));
+    }
+    assert!(Connections::from_tile(b'S').is_none());
+    assert!(Connections::all().to_tile().is_none());
+
+    let drawn: String = b"|-LJ7F."
+        .iter()
+        .map(|&tile| Connections::from_tile(tile).unwrap().to_box_drawing())
+        .collect();
+    assert!(drawn == "│─└┘┐┌ ");
+}
+
+#[test]
+fn pipes_directions() {
+    use Connections as C;
+
+    assert!(C::NORTH.opposite() == C::SOUTH);
+    assert!((C::EAST | C::SOUTH).opposite() == C::WEST | C::NORTH);
+    assert!(C::direction_to([1, 1], [1, 0]) == Some(C::WEST));
+    assert!(C::direction_to([1, 1], [0, 0]).is_none());
+    assert!(C::NORTH.neighbour([0, 3]) == [usize::MAX, 3]);
+
+    let [vertical, horizontal, corner] = [b'|', b'-', b'F'].map(|t| C::from_tile(t).unwrap());
+    assert!(corner.connects_with(vertical, C::SOUTH));
+    assert!(corner.connects_with(horizontal, C::EAST));
+    assert!(!corner.connects_with(vertical, C::EAST));
+    assert!(!corner.compatible_with(vertical, C::EAST));
+    assert!(horizontal.compatible_with(horizontal, C::NORTH));
+}
+
+#[test]
+fn pipes_loop_from_start() {
+    let lines = [
+        ".....",
+        ".S-7.",
+        ".|.|.",
+        ".L-J.",
+        ".....",
+    ];
+    let board = crate::InputBuffer::new(lines.join("\n")).to_2d_board();
+    assert!(start_directions(board.view(), [1, 1]) == [Connections::EAST, Connections::SOUTH]);
+    assert!(loop_from_start(board.view(), [1, 1]).unwrap() == [
+        [1, 1], [1, 2], [1, 3], [2, 3], [3, 3], [3, 2], [3, 1], [2, 1]
+    ]);
+
+    let mut broken = board.clone();
+    broken[[3, 2]] = b'|';
+    assert!(loop_from_start(broken.view(), [1, 1]).is_none());
+    broken[[1, 2]] = b'.';
+    broken[[2, 1]] = b'.';
+    assert!(start_directions(broken.view(), [1, 1]).is_empty());
+    assert!(loop_from_start(broken.view(), [1, 1]).is_none());
+}
+
+#[test]
+fn pipes_enclosed_tiles() {
+    let lines = [
+        "..........",
+        ".F------7.",
+        ".|F----7|.",
+        ".||....||.",
+        ".||....||.",
+        ".|L-7F-J|.",
+        ".|..||..|.",
+        ".L--JL--J.",
+        "..........",
+    ];
+    let board = crate::InputBuffer::new(lines.join("\n")).to_2d_board();
+
+    for scale in [2, 3, 4] {
+        let enclosed = enclosed_tiles(board.view(), scale);
+        let enclosed_positions = enclosed
+            .indexed_iter()
+            .filter(|(_, &inside)| inside)
+            .map(|((y, x), _)| [y, x])
+            .collect_vec();
+        assert!(enclosed_positions == [[6, 2], [6, 3], [6, 6], [6, 7]]);
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::IsTerminal;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Progress of a long computation, drawn as a bar on stderr when it is a terminal.
+/// Hidden progress is still counted, but never drawn.
+pub struct Progress {
+    bar: ProgressBar
+}
+
+impl Progress {
+    pub fn new(total: u64) -> Self {
+        if !std::io::stderr().is_terminal() {
+            return Self::hidden(total);
+        }
+
+        let bar = ProgressBar::new(total);
+        bar.set_style(ProgressStyle::with_template("{bar:40} {pos}/{len} [{elapsed_precise}, {eta} left] {msg}").unwrap());
+        Progress { bar }
+    }
+
+    pub fn hidden(total: u64) -> Self {
+        let bar = ProgressBar::hidden();
+        bar.set_length(total);
+        Progress { bar }
+    }
+
+    /// Hidden with `--quiet`, or with `--json` so that only the JSON is printed
+    pub fn from_args(total: u64) -> Self {
+        if std::env::args().any(|arg| arg == "--quiet" || arg == "--json") {
+            Self::hidden(total)
+        } else {
+            Self::new(total)
+        }
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.bar.set_length(total);
+    }
+
+    pub fn increment(&self, delta: u64) {
+        self.bar.inc(delta);
+    }
+
+    pub fn message(&self, message: impl Into<String>) {
+        self.bar.set_message(message.into());
+    }
+
+    pub fn position(&self) -> u64 {
+        self.bar.position()
+    }
+}
+
+impl Drop for Progress {
+    /// Clears the bar, so it does not mix with the results
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[test]
+fn progress_hidden() {
+    let progress = Progress::hidden(10);
+    progress.increment(3);
+    progress.message("counted, not drawn");
+    progress.set_total(20);
+    progress.increment(4);
+    assert!(progress.position() == 7);
+}
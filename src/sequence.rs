@@ -0,0 +1,269 @@
+use itertools::Itertools;
+use num::{BigInt, BigRational, CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Integer, One, Signed, Zero};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SequenceError {
+    #[error("Sequence is empty")]
+    Empty,
+
+    #[error("Sequence of length {0} is not a polynomial of a lower degree")]
+    NotPolynomial(usize),
+
+    #[error("Sequence does not fit in its number type")]
+    Overflow
+}
+
+/// A polynomial sequence described by its Newton forward differences at the first term:
+/// `a(n) = sum over j of C(n, j) * differences[j]`.
+/// All the arithmetic is checked, values outside of `T` give `SequenceError::Overflow`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolynomialSequence<T> {
+    differences: Vec<T>
+}
+
+impl<T> PolynomialSequence<T>
+    where T: Clone + Integer + FromPrimitive + CheckedAdd + CheckedSub + CheckedMul
+{
+    /// Finds the lowest degree polynomial matching all the values.
+    /// The highest differences must be constant over at least two elements,
+    /// otherwise the sequence is too short to tell it is a polynomial.
+    pub fn fit(values: &[T]) -> Result<Self, SequenceError> {
+        let sequence = Self::interpolate(values)?;
+        if sequence.degree() + 1 >= values.len() && values.len() > 1 {
+            return Err(SequenceError::NotPolynomial(values.len()));
+        }
+        Ok(sequence)
+    }
+
+    /// Finds the lowest degree polynomial matching all the values.
+    /// Any sequence of length `n` fits a polynomial of degree `n - 1`.
+    pub fn interpolate(values: &[T]) -> Result<Self, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+
+        let mut differences = Vec::new();
+        let mut layer = values.to_vec();
+
+        while !layer.iter().all_equal() {
+            differences.push(layer[0].clone());
+            layer = layer
+                .iter()
+                .tuple_windows()
+                .map(|(a, b)| b.checked_sub(a))
+                .collect::<Option<_>>()
+                .ok_or(SequenceError::Overflow)?;
+        }
+        differences.push(layer[0].clone());
+
+        Ok(PolynomialSequence { differences })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.differences.len() - 1
+    }
+
+    /// Leading terms of the difference table, `differences()[j]` is the j-th difference at the first term
+    pub fn differences(&self) -> &[T] {
+        &self.differences
+    }
+
+    /// The n-th term (0-based) of the sequence, negative `n` extrapolates backwards
+    pub fn term(&self, n: i64) -> Result<T, SequenceError> {
+        self.checked_term(n).ok_or(SequenceError::Overflow)
+    }
+
+    fn checked_term(&self, n: i64) -> Option<T> {
+        let n = T::from_i64(n)?;
+        let mut binomial = T::one(); // C(n, j)
+        let mut result = T::zero();
+
+        for (j, difference) in self.differences.iter().enumerate() {
+            if j > 0 {
+                // C(n, j) = C(n, j-1) * (n - j + 1) / j, the division is always exact
+                let j = T::from_usize(j)?;
+                binomial = binomial.checked_mul(&n.checked_sub(&j)?.checked_add(&T::one())?)? / j;
+            }
+            result = result.checked_add(&binomial.checked_mul(difference)?)?;
+        }
+
+        Some(result)
+    }
+}
+
+impl<T> PolynomialSequence<T>
+    where T: Clone,
+          BigInt: From<T>
+{
+    /// The polynomial in the power basis, `p(n)` is the n-th term (0-based)
+    pub fn polynomial(&self) -> Polynomial {
+        let mut coefficients = vec![BigRational::zero()];
+        let mut binomial = vec![BigRational::one()]; // C(n, j) as a polynomial of n
+
+        for (j, difference) in self.differences.iter().enumerate() {
+            let difference = BigRational::from(BigInt::from(difference.clone()));
+            coefficients.resize(binomial.len(), BigRational::zero());
+            for (c, b) in coefficients.iter_mut().zip(binomial.iter()) {
+                *c += b * &difference;
+            }
+
+            // C(n, j+1) = C(n, j) * (n - j) / (j + 1)
+            let j = BigRational::from_usize(j).unwrap();
+            let mut next = vec![BigRational::zero(); binomial.len() + 1];
+            for (k, b) in binomial.iter().enumerate() {
+                next[k + 1] += b;
+                next[k] -= b * &j;
+            }
+            let divisor = j + BigRational::one();
+            binomial = next.into_iter().map(|b| b / &divisor).collect();
+        }
+
+        Polynomial::new(coefficients)
+    }
+}
+
+/// Polynomial with rational coefficients, `coefficients[k]` stands next to `n^k`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// Degree of the polynomial, 0 for the zero polynomial
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    pub fn evaluate(&self, n: &BigRational) -> BigRational {
+        // Horner's method
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * n + c)
+    }
+}
+
+impl std::fmt::Display for Polynomial {
+    /// Formats as e.g. `1/2 n^2 + 3/2 n + 1`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.coefficients.is_empty() {
+            return write!(f, "0");
+        }
+
+        let terms = self.coefficients
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, c)| !c.is_zero());
+
+        for (i, (power, c)) in terms.enumerate() {
+            match (i, c.is_negative()) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {},
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+
+            let c = c.abs();
+            if !c.is_one() || power == 0 {
+                write!(f, "{}", c)?;
+            }
+            match power {
+                0 => {},
+                1 if c.is_one() => write!(f, "n")?,
+                1 => write!(f, " n")?,
+                _ if c.is_one() => write!(f, "n^{}", power)?,
+                _ => write!(f, " n^{}", power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn sequence_degree() {
+    let linear = PolynomialSequence::fit(&[0i128, 3, 6, 9, 12, 15]).unwrap();
+    let quadratic = PolynomialSequence::fit(&[1i128, 3, 6, 10, 15, 21]).unwrap();
+    let cubic = PolynomialSequence::fit(&[10i128, 13, 16, 21, 30, 45]).unwrap();
+    assert!(linear.degree() == 1);
+    assert!(quadratic.degree() == 2);
+    assert!(cubic.degree() == 3);
+    assert!(PolynomialSequence::fit(&[7i128, 7, 7]).unwrap().degree() == 0);
+}
+
+#[test]
+fn sequence_extrapolation() {
+    let sequence = PolynomialSequence::fit(&[10i128, 13, 16, 21, 30, 45]).unwrap();
+    assert!(sequence.term(6) == Ok(68));
+    assert!(sequence.term(-1) == Ok(5));
+    assert!((0..6).map(|n| sequence.term(n).unwrap()).collect_vec() == [10, 13, 16, 21, 30, 45]);
+
+    // squares, far away in both directions
+    let squares = PolynomialSequence::fit(&[0i128, 1, 4, 9]).unwrap();
+    assert!(squares.term(1_000_000_000) == Ok(1_000_000_000_000_000_000));
+    assert!(squares.term(-7) == Ok(49));
+}
+
+#[test]
+fn sequence_big_int() {
+    use num::BigInt;
+
+    // cubes of numbers around 10^30 do not fit in i128
+    let base = BigInt::from(10).pow(30);
+    let cubes = (0..5)
+        .map(|i: i32| (base.clone() + i).pow(3))
+        .collect_vec();
+    let sequence = PolynomialSequence::fit(&cubes).unwrap();
+    assert!(sequence.degree() == 3);
+    assert!(sequence.term(100) == Ok((base + 100i32).pow(3)));
+}
+
+#[test]
+fn sequence_not_polynomial() {
+    assert!(PolynomialSequence::<i128>::fit(&[]) == Err(SequenceError::Empty));
+    assert!(PolynomialSequence::fit(&[1i128, 2, 4, 8, 16]) == Err(SequenceError::NotPolynomial(5)));
+    assert!(PolynomialSequence::interpolate(&[1i128, 2, 4, 8, 16]).unwrap().term(5) == Ok(31));
+}
+
+#[test]
+fn sequence_overflow() {
+    // the squares of numbers past about 3 * 10^9 do not fit in i64
+    let squares = PolynomialSequence::fit(&[0i64, 1, 4, 9]).unwrap();
+    assert!(squares.term(4_000_000_000) == Err(SequenceError::Overflow));
+    assert!(squares.term(-4_000_000_000) == Err(SequenceError::Overflow));
+    assert!(squares.term(3_000_000) == Ok(9_000_000_000_000));
+
+    assert!(PolynomialSequence::interpolate(&[i64::MIN, i64::MAX]) == Err(SequenceError::Overflow));
+    assert!(PolynomialSequence::interpolate(&[i64::MAX, i64::MAX]).unwrap().term(1_000) == Ok(i64::MAX));
+}
+
+#[test]
+fn sequence_polynomial() {
+    let rational = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+
+    // triangular numbers shifted by one: (n+1)(n+2)/2
+    let polynomial = PolynomialSequence::fit(&[1i128, 3, 6, 10, 15, 21]).unwrap().polynomial();
+    assert!(polynomial.coefficients() == [rational(1, 1), rational(3, 2), rational(1, 2)]);
+    assert!(polynomial.degree() == 2);
+    assert!(polynomial.to_string() == "1/2 n^2 + 3/2 n + 1");
+    assert!(polynomial.evaluate(&rational(6, 1)) == rational(28, 1));
+
+    let polynomial = PolynomialSequence::fit(&[0i128, -1, -4, -9]).unwrap().polynomial();
+    assert!(polynomial.to_string() == "-n^2");
+    let polynomial = PolynomialSequence::fit(&[5i128, 3, 1]).unwrap().polynomial();
+    assert!(polynomial.to_string() == "-2 n + 5");
+    let polynomial = PolynomialSequence::fit(&[0i128, 0, 0]).unwrap().polynomial();
+    assert!(polynomial.to_string() == "0");
+}